@@ -1,21 +1,116 @@
 const SHADERS_DIR: &str = "shaders/";
-const OUTPUT_FILE: &str = "src/shaders_embedded.rs";
+const SHADERS_OUTPUT_FILE: &str = "src/shaders_embedded.rs";
+
+const ASSETS_DIR: &str = "assets/";
+const ASSETS_OUTPUT_FILE: &str = "src/assets_embedded.rs";
 
 fn main() {
     println!("cargo:rerun-if-changed={}", SHADERS_DIR);
+    println!("cargo:rerun-if-changed={}", ASSETS_DIR);
+
+    embed_directory(
+        SHADERS_DIR,
+        SHADERS_OUTPUT_FILE,
+        "SHADER_FILES",
+        "&str",
+        "include_str",
+    );
+    embed_directory(
+        ASSETS_DIR,
+        ASSETS_OUTPUT_FILE,
+        "ASSET_FILES",
+        "&[u8]",
+        "include_bytes",
+    );
+
+    emit_build_info();
+}
+
+/// Exposes the git hash & build date to the application via `GIT_HASH` & `BUILD_DATE` environment variables.
+///
+/// Note that the build date is only updated when the build script reruns,
+/// i.e. when shaders, assets or the checked out commit changed.
+fn emit_build_info() {
+    let git = |args: &[&str]| {
+        std::process::Command::new("git")
+            .args(args)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .and_then(|output| String::from_utf8(output.stdout).ok())
+            .map(|output| output.trim().to_owned())
+    };
+
+    let git_hash = git(&["rev-parse", "--short", "HEAD"]).unwrap_or_else(|| "unknown".to_owned());
+    println!("cargo:rustc-env=GIT_HASH={git_hash}");
+
+    // Rerun whenever a different commit is checked out.
+    // Only for paths that exist, since cargo would otherwise rerun the build script on every build.
+    for path in ["HEAD", "refs/heads"] {
+        if let Some(path) = git(&["rev-parse", "--git-path", path]) {
+            if std::path::Path::new(&path).exists() {
+                println!("cargo:rerun-if-changed={path}");
+            }
+        }
+    }
+
+    // Respect `SOURCE_DATE_EPOCH` for reproducible builds.
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+    let seconds_since_epoch = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|epoch| epoch.parse::<u64>().ok())
+        .unwrap_or_else(|| {
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |duration| duration.as_secs())
+        });
+    let (year, month, day) = civil_from_days((seconds_since_epoch / 86400) as i64);
+    println!("cargo:rustc-env=BUILD_DATE={year:04}-{month:02}-{day:02}");
+}
+
+/// Converts days since 1970-01-01 to a (year, month, day) date.
+///
+/// See <https://howardhinnant.github.io/date_algorithms.html#civil_from_days>.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    (year, month, day)
+}
 
-    let mut shader_count = 0;
-    let shader_files = walkdir::WalkDir::new(SHADERS_DIR)
+/// Writes a rust file that embeds all files in a directory as a constant array of `(name, content)` tuples.
+fn embed_directory(
+    directory: &str,
+    output_file: &str,
+    const_name: &str,
+    content_type: &str,
+    include_macro: &str,
+) {
+    let mut file_count = 0;
+    let files = walkdir::WalkDir::new(directory)
         .into_iter()
         .filter_map(|entry| {
+            // The directory may not exist at all, which is fine.
             let Ok(entry) = entry else {
                 return None;
             };
             if entry.file_type().is_file() {
                 let path = entry.path().to_str().unwrap().to_owned().replace('\\', "/");
-                let name = path.strip_prefix(SHADERS_DIR).unwrap();
-                shader_count += 1;
-                Some(format!(r#"    ("{name}", include_str!("../{path}")),"#))
+                let name = path.strip_prefix(directory).unwrap();
+                file_count += 1;
+                Some(format!(r#"    ("{name}", {include_macro}!("../{path}")),"#))
             } else {
                 None
             }
@@ -27,11 +122,11 @@ fn main() {
         r#"// This file is autogenerated via build.rs.
 // DO NOT EDIT.
 
-pub const SHADER_FILES: [(&str, &str); {shader_count}] = [
-{shader_files}
+pub const {const_name}: [(&str, {content_type}); {file_count}] = [
+{files}
 ];
 "#,
     );
 
-    std::fs::write(OUTPUT_FILE, contents).unwrap();
+    std::fs::write(output_file, contents).unwrap();
 }
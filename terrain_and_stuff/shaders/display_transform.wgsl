@@ -3,13 +3,30 @@
 @group(0) @binding(0)
 var hdr_backbuffer: texture_2d<f32>;
 
-@fragment
-fn fs_main(@location(0) texcoord: vec2<f32>) -> @location(0) vec4<f32> {
+fn display_transform(texcoord: vec2<f32>) -> vec3<f32> {
     let texture_dimensions = vec2f(textureDimensions(hdr_backbuffer).xy);
     let texel_coords = vec2u(texcoord * texture_dimensions);
     let hdr_backbuffer_color = textureLoad(hdr_backbuffer, texel_coords, 0);
 
     // TODO: actual display transform!
 
-    return vec4f(srgb_from_linear(hdr_backbuffer_color.rgb), 1.0);
+    return srgb_from_linear(hdr_backbuffer_color.rgb);
+}
+
+// Interleaved gradient noise, see "Next Generation Post Processing in Call of Duty: Advanced Warfare" by Jorge Jimenez.
+// Screen space noise with blue noise like properties that doesn't need a texture.
+fn interleaved_gradient_noise(pixel: vec2f) -> f32 {
+    return fract(52.9829189 * fract(dot(pixel, vec2f(0.06711056, 0.00583715))));
+}
+
+@fragment
+fn fs_main(@location(0) texcoord: vec2<f32>) -> @location(0) vec4<f32> {
+    return vec4f(display_transform(texcoord), 1.0);
+}
+
+// Dithers the output by up to half a quantization step of an 8 bit target in each direction, hiding banding in smooth gradients.
+@fragment
+fn fs_main_dithered(@builtin(position) position: vec4f, @location(0) texcoord: vec2<f32>) -> @location(0) vec4<f32> {
+    let dither = (interleaved_gradient_noise(position.xy) - 0.5) / 255.0;
+    return vec4f(display_transform(texcoord) + dither, 1.0);
 }
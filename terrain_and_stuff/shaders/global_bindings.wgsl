@@ -0,0 +1,7 @@
+// Bindings that are shared by all scene rendering passes.
+// Must match `GlobalBindings` in `global_bindings.rs`.
+
+#import "generated.wgsl"::FrameUniformBuffer
+
+@group(0) @binding(0)
+var<uniform> frame: FrameUniformBuffer;
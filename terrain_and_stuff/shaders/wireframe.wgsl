@@ -0,0 +1,6 @@
+// Constant color fragment shader for wireframe variants of pipelines.
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+    return vec4<f32>(1.0, 1.0, 1.0, 1.0);
+}
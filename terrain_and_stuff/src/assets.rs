@@ -0,0 +1,112 @@
+//! Locating & loading of runtime assets.
+//!
+//! On native, all assets are loaded from disk relative to the asset root, which is resolved in this order:
+//! * the directory given by the [`ASSET_ROOT_ENV_VAR`] environment variable
+//! * the directory next to the executable, if it contains an `assets` or `shaders` directory (packaged builds)
+//! * the crate's manifest directory (development builds, independent of the working directory)
+//!
+//! On the web, assets are embedded into the binary via `build.rs`.
+
+use std::{
+    borrow::Cow,
+    path::{Path, PathBuf},
+};
+
+/// Environment variable that can be used to override the asset root directory.
+#[cfg(not(target_arch = "wasm32"))]
+pub const ASSET_ROOT_ENV_VAR: &str = "TERRAIN_AND_STUFF_ROOT";
+
+/// Name of the directory below the asset root that contains all non-shader assets.
+#[cfg(not(target_arch = "wasm32"))]
+const ASSETS_DIR_NAME: &str = "assets";
+
+/// Name of the directory below the asset root that contains all shaders.
+#[cfg(not(target_arch = "wasm32"))]
+const SHADERS_DIR_NAME: &str = "shaders";
+
+/// Errors loading an asset.
+#[derive(thiserror::Error, Debug)]
+pub enum AssetError {
+    /// Reading the asset from disk failed, e.g. because it doesn't exist.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[error("Failed to read asset {path:?}: {err}")]
+    FailedToRead {
+        /// Path the asset was requested with.
+        path: PathBuf,
+        /// Underlying I/O error.
+        err: std::io::Error,
+    },
+
+    /// The asset wasn't embedded into the binary.
+    #[cfg(target_arch = "wasm32")]
+    #[error("Failed to find asset {path:?} in embedded assets.")]
+    EmbeddedAssetNotFound {
+        /// Path the asset was requested with.
+        path: PathBuf,
+    },
+}
+
+/// Root directory all assets & shaders are loaded from.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn asset_root() -> &'static Path {
+    static ASSET_ROOT: std::sync::OnceLock<PathBuf> = std::sync::OnceLock::new();
+
+    ASSET_ROOT.get_or_init(|| {
+        let asset_root = resolve_asset_root();
+        log::info!("Using asset root {asset_root:?}");
+        asset_root
+    })
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn resolve_asset_root() -> PathBuf {
+    if let Some(asset_root) = std::env::var_os(ASSET_ROOT_ENV_VAR) {
+        return PathBuf::from(asset_root);
+    }
+
+    if let Some(exe_dir) = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf))
+    {
+        if exe_dir.join(ASSETS_DIR_NAME).is_dir() || exe_dir.join(SHADERS_DIR_NAME).is_dir() {
+            return exe_dir;
+        }
+    }
+
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+}
+
+/// Directory containing all non-shader assets.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn assets_dir() -> PathBuf {
+    asset_root().join(ASSETS_DIR_NAME)
+}
+
+/// Directory containing all shaders.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn shaders_dir() -> PathBuf {
+    asset_root().join(SHADERS_DIR_NAME)
+}
+
+/// Loads the content of an asset.
+///
+/// `path` is relative to the assets directory.
+pub fn load_asset(path: &Path) -> Result<Cow<'static, [u8]>, AssetError> {
+    #[cfg(target_arch = "wasm32")]
+    {
+        let path_str = path.to_str().unwrap_or_default().replace('\\', "/");
+        crate::assets_embedded::ASSET_FILES
+            .iter()
+            .find_map(|(name, content)| (*name == path_str).then_some(Cow::Borrowed(*content)))
+            .ok_or(AssetError::EmbeddedAssetNotFound {
+                path: path.to_path_buf(),
+            })
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let path = assets_dir().join(path);
+        std::fs::read(&path)
+            .map(Cow::Owned)
+            .map_err(|err| AssetError::FailedToRead { path, err })
+    }
+}
@@ -0,0 +1,185 @@
+//! Benchmark mode: renders a fixed number of frames, writes a frame time report and exits.
+//!
+//! Besides the CPU frame time, the report contains the GPU time of every timestamp query scope.
+//! Native only.
+
+use std::{path::PathBuf, time::Duration};
+
+/// Frames at the start of a benchmark that are not measured.
+///
+/// Gives shaders a chance to compile and drivers a chance to clock up.
+const NUM_WARMUP_FRAMES: u32 = 60;
+
+/// Summary statistics of a single metric, all in milliseconds.
+struct MetricStatistics {
+    num_samples: usize,
+    min: f64,
+    avg: f64,
+    p95: f64,
+    max: f64,
+}
+
+impl MetricStatistics {
+    fn from_durations(durations: &[Duration]) -> Option<Self> {
+        if durations.is_empty() {
+            return None;
+        }
+
+        let mut milliseconds = durations
+            .iter()
+            .map(|duration| duration.as_secs_f64() * 1000.0)
+            .collect::<Vec<_>>();
+        milliseconds.sort_by(f64::total_cmp);
+
+        let p95_index =
+            ((milliseconds.len() as f64 * 0.95).ceil() as usize).clamp(1, milliseconds.len()) - 1;
+
+        Some(Self {
+            num_samples: milliseconds.len(),
+            min: milliseconds[0],
+            avg: milliseconds.iter().sum::<f64>() / milliseconds.len() as f64,
+            p95: milliseconds[p95_index],
+            max: milliseconds[milliseconds.len() - 1],
+        })
+    }
+}
+
+pub struct Benchmark {
+    num_frames: u32,
+    report_path: PathBuf,
+
+    num_started_frames: u32,
+    frame_times: Vec<Duration>,
+
+    /// GPU times per timestamp query scope, in order of first appearance.
+    gpu_scope_times: Vec<(&'static str, Vec<Duration>)>,
+}
+
+impl Benchmark {
+    pub fn new(num_frames: u32, report_path: PathBuf) -> Self {
+        log::info!(
+            "Benchmarking {num_frames} frames (after {NUM_WARMUP_FRAMES} warmup frames), report goes to {report_path:?}"
+        );
+
+        Self {
+            num_frames,
+            report_path,
+            num_started_frames: 0,
+            frame_times: Vec::with_capacity(num_frames as usize),
+            gpu_scope_times: Vec::new(),
+        }
+    }
+
+    /// Records the time a frame took, along with all GPU scope times that were read back since the last frame.
+    ///
+    /// Returns `true` once all frames have been recorded.
+    pub fn record_frame(
+        &mut self,
+        frame_time: Duration,
+        gpu_scope_times: Vec<(&'static str, Duration)>,
+    ) -> bool {
+        self.num_started_frames += 1;
+        if self.num_started_frames > NUM_WARMUP_FRAMES {
+            self.frame_times.push(frame_time);
+
+            for (label, duration) in gpu_scope_times {
+                if let Some((_, durations)) = self
+                    .gpu_scope_times
+                    .iter_mut()
+                    .find(|(scope_label, _)| *scope_label == label)
+                {
+                    durations.push(duration);
+                } else {
+                    self.gpu_scope_times.push((label, vec![duration]));
+                }
+            }
+        }
+
+        self.frame_times.len() >= self.num_frames as usize
+    }
+
+    /// Writes the report, either as JSON or CSV depending on the file extension of the report path.
+    pub fn write_report(&self) -> anyhow::Result<()> {
+        let metrics = std::iter::once(("frame_time".to_owned(), &self.frame_times))
+            .chain(
+                self.gpu_scope_times
+                    .iter()
+                    .map(|(label, durations)| (format!("gpu_{}", label.to_lowercase()), durations)),
+            )
+            .filter_map(|(name, durations)| {
+                Some((name, MetricStatistics::from_durations(durations)?))
+            })
+            .collect::<Vec<_>>();
+
+        let is_json = self
+            .report_path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("json"));
+
+        let report = if is_json {
+            let metrics = metrics
+                .iter()
+                .map(|(name, s)| {
+                    format!(
+                        r#"    "{name}": {{ "num_frames": {}, "min_ms": {}, "avg_ms": {}, "p95_ms": {}, "max_ms": {} }}"#,
+                        s.num_samples, s.min, s.avg, s.p95, s.max
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(",\n");
+            format!(
+                "{{\n  \"num_frames\": {},\n  \"metrics\": {{\n{metrics}\n  }}\n}}\n",
+                self.frame_times.len()
+            )
+        } else {
+            let mut report = "metric,num_frames,min_ms,avg_ms,p95_ms,max_ms\n".to_owned();
+            for (name, s) in &metrics {
+                report += &format!(
+                    "{name},{},{},{},{},{}\n",
+                    s.num_samples, s.min, s.avg, s.p95, s.max
+                );
+            }
+            report
+        };
+
+        std::fs::write(&self.report_path, &report)?;
+        log::info!(
+            "Wrote benchmark report to {:?}:\n{report}",
+            self.report_path
+        );
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_has_a_row_per_gpu_scope() {
+        let report_path = std::env::temp_dir().join("terrain_and_stuff_benchmark_report.csv");
+        let mut benchmark = Benchmark::new(2, report_path.clone());
+
+        let ms = Duration::from_millis;
+        for _ in 0..NUM_WARMUP_FRAMES {
+            assert!(!benchmark.record_frame(ms(100), vec![("Scene", ms(100))]));
+        }
+        assert!(!benchmark.record_frame(ms(10), vec![("Scene", ms(4)), ("Output", ms(1))]));
+        assert!(benchmark.record_frame(ms(20), vec![("Scene", ms(6))]));
+
+        benchmark.write_report().unwrap();
+        let report = std::fs::read_to_string(&report_path).unwrap();
+        std::fs::remove_file(&report_path).unwrap();
+
+        assert_eq!(
+            report.lines().collect::<Vec<_>>(),
+            [
+                "metric,num_frames,min_ms,avg_ms,p95_ms,max_ms",
+                "frame_time,2,10,15,20,20",
+                "gpu_scene,2,4,5,6,6",
+                "gpu_output,1,1,1,1,1",
+            ]
+        );
+    }
+}
@@ -0,0 +1,112 @@
+//! Command line interface, native only.
+
+use std::path::PathBuf;
+
+use crate::{
+    benchmark::Benchmark,
+    config::{Config, PresentMode},
+    video_recorder::VideoSettings,
+    StartupOptions,
+};
+
+#[derive(clap::Parser)]
+#[command(version, about = "Terrain & atmosphere rendering playground.")]
+pub struct CliArgs {
+    /// Config file to start with instead of the active config profile.
+    ///
+    /// Settings are not saved on exit when starting with a config file, and config profiles can't be switched.
+    #[arg(long, value_name = "PATH", conflicts_with = "profile")]
+    config: Option<PathBuf>,
+
+    /// Config profile to activate, created with default settings if it doesn't exist yet.
+    ///
+    /// Names may only contain ASCII letters, digits, `_` and `-`.
+    #[arg(long, value_name = "NAME")]
+    profile: Option<String>,
+
+    /// Initial window width.
+    #[arg(long, default_value_t = crate::WIDTH)]
+    width: usize,
+
+    /// Initial window height.
+    #[arg(long, default_value_t = crate::HEIGHT)]
+    height: usize,
+
+    /// Records all input to a file, for later replay via `--replay-input`.
+    #[arg(long, value_name = "PATH", conflicts_with = "replay_input")]
+    record_input: Option<PathBuf>,
+
+    /// Replays input recorded via `--record-input` and exits once the recording ends.
+    ///
+    /// For the replay to match the recording, start it with the same config, e.g. via `--config`.
+    #[arg(long, value_name = "PATH")]
+    replay_input: Option<PathBuf>,
+
+    /// Renders the given number of frames, writes a frame time report and exits.
+    ///
+    /// Uses the config given via `--config` or the default config, but always with vsync disabled.
+    /// The report contains GPU times per pass, so this fails if the adapter doesn't support timestamp queries.
+    #[arg(long, value_name = "FRAMES", value_parser = clap::value_parser!(u32).range(1..))]
+    benchmark: Option<u32>,
+
+    /// Path of the benchmark report, written as JSON if the extension is `.json` and as CSV otherwise.
+    #[arg(long, value_name = "PATH", default_value = "benchmark_report.csv")]
+    benchmark_report: PathBuf,
+
+    /// Records all presented frames to a video file via `ffmpeg`, until exit or until toggled off with F8.
+    ///
+    /// Container & codec are picked by `ffmpeg` based on the file extension, e.g. `.mp4` or `.webm`.
+    #[arg(long, value_name = "PATH")]
+    record_video: Option<PathBuf>,
+
+    /// Frame rate of recorded videos, the simulation advances by exactly one frame at this rate per rendered frame.
+    #[arg(
+        long,
+        default_value_t = VideoSettings::default().fps,
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    video_fps: u32,
+
+    /// Bitrate of recorded videos in `ffmpeg` notation.
+    #[arg(long, default_value_t = VideoSettings::default().bitrate)]
+    video_bitrate: String,
+
+    /// Composes & validates all shaders, then exits without opening a window.
+    ///
+    /// Fails if any shader has errors.
+    #[arg(long)]
+    pub validate_shaders: bool,
+}
+
+impl CliArgs {
+    pub fn benchmark(&self) -> Option<Benchmark> {
+        self.benchmark
+            .map(|num_frames| Benchmark::new(num_frames, self.benchmark_report.clone()))
+    }
+
+    pub fn into_startup_options(self) -> anyhow::Result<StartupOptions> {
+        let mut config = self
+            .config
+            .map(|path| Config::load_from_file(&path))
+            .transpose()?;
+
+        if self.benchmark.is_some() {
+            // Benchmarks should be reproducible, so never use whatever profile happens to be active.
+            config.get_or_insert_with(Config::default).present_mode = PresentMode::AutoNoVsync;
+        }
+
+        Ok(StartupOptions {
+            window_width: self.width,
+            window_height: self.height,
+            profile: self.profile,
+            config,
+            record_input: self.record_input,
+            replay_input: self.replay_input,
+            video_settings: VideoSettings {
+                fps: self.video_fps,
+                bitrate: self.video_bitrate,
+            },
+            record_video: self.record_video,
+        })
+    }
+}
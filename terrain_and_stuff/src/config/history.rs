@@ -0,0 +1,179 @@
+use super::Config;
+
+/// Undo/redo history of config changes.
+///
+/// Stores full config snapshots, configs are small enough for that.
+/// Changes are detected by comparing against the last recorded config,
+/// so all settings are covered no matter where they're changed.
+pub struct ConfigHistory {
+    undo_stack: Vec<Config>,
+    redo_stack: Vec<Config>,
+    current: Config,
+}
+
+impl ConfigHistory {
+    /// Oldest changes are forgotten beyond this many undo steps.
+    const MAX_UNDO_STEPS: usize = 100;
+
+    pub fn new(current: Config) -> Self {
+        Self {
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            current,
+        }
+    }
+
+    /// Forgets all history, e.g. after switching to a different config profile.
+    pub fn reset(&mut self, current: Config) {
+        *self = Self::new(current);
+    }
+
+    /// Records a change if the config differs from the last recorded one.
+    pub fn record(&mut self, config: &Config) {
+        if *config == self.current {
+            return;
+        }
+
+        self.undo_stack
+            .push(std::mem::replace(&mut self.current, config.clone()));
+        if self.undo_stack.len() > Self::MAX_UNDO_STEPS {
+            self.undo_stack.remove(0);
+        }
+        self.redo_stack.clear();
+    }
+
+    /// Steps back to the previous config, returning it if there was one.
+    pub fn undo(&mut self) -> Option<&Config> {
+        let previous = self.undo_stack.pop()?;
+        let undone = std::mem::replace(&mut self.current, previous);
+        log::info!("Undo: {}", describe_changes(&undone, &self.current));
+        self.redo_stack.push(undone);
+        Some(&self.current)
+    }
+
+    /// Steps forward to the last undone config, returning it if there was one.
+    pub fn redo(&mut self) -> Option<&Config> {
+        let next = self.redo_stack.pop()?;
+        let redone = std::mem::replace(&mut self.current, next);
+        log::info!("Redo: {}", describe_changes(&redone, &self.current));
+        self.undo_stack.push(redone);
+        Some(&self.current)
+    }
+
+    /// Logs all recorded changes, oldest first.
+    pub fn log_history(&self) {
+        let configs = self
+            .undo_stack
+            .iter()
+            .chain(std::iter::once(&self.current))
+            .collect::<Vec<_>>();
+        let undo_history = configs
+            .windows(2)
+            .map(|pair| format!("  {}", describe_changes(pair[0], pair[1])))
+            .collect::<Vec<_>>();
+
+        let mut redo_history = Vec::new();
+        let mut previous = &self.current;
+        for next in self.redo_stack.iter().rev() {
+            redo_history.push(format!("  (undone) {}", describe_changes(previous, next)));
+            previous = next;
+        }
+
+        if undo_history.is_empty() && redo_history.is_empty() {
+            log::info!("No config changes to undo or redo.");
+        } else {
+            log::info!(
+                "Config history:\n{}",
+                undo_history
+                    .into_iter()
+                    .chain(redo_history)
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            );
+        }
+    }
+}
+
+/// Lists the settings that differ between two configs, based on their RON representation.
+fn describe_changes(from: &Config, to: &Config) -> String {
+    let (Ok(from), Ok(to)) = (from.to_ron(), to.to_ron()) else {
+        return "unknown changes".to_owned();
+    };
+
+    from.lines()
+        .zip(to.lines())
+        .filter(|(from, to)| from != to)
+        .map(|(from, to)| {
+            let to = to.split_once(':').map_or(to, |(_, value)| value);
+            format!(
+                "{} ->{}",
+                from.trim().trim_end_matches(','),
+                to.trim_end_matches(',')
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(time_scale: f32) -> Config {
+        Config {
+            time_scale,
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn record_undo_redo() {
+        let mut history = ConfigHistory::new(config(1.0));
+        history.record(&config(1.0));
+        assert!(history.undo().is_none(), "Unchanged config was recorded");
+
+        history.record(&config(2.0));
+        history.record(&config(3.0));
+
+        assert_eq!(history.undo(), Some(&config(2.0)));
+        assert_eq!(history.undo(), Some(&config(1.0)));
+        assert_eq!(history.undo(), None);
+
+        assert_eq!(history.redo(), Some(&config(2.0)));
+        assert_eq!(history.redo(), Some(&config(3.0)));
+        assert_eq!(history.redo(), None);
+    }
+
+    #[test]
+    fn new_change_clears_redo() {
+        let mut history = ConfigHistory::new(config(1.0));
+        history.record(&config(2.0));
+        assert_eq!(history.undo(), Some(&config(1.0)));
+
+        history.record(&config(4.0));
+        assert_eq!(history.redo(), None);
+        assert_eq!(history.undo(), Some(&config(1.0)));
+    }
+
+    #[test]
+    fn undo_steps_are_capped() {
+        let mut history = ConfigHistory::new(config(0.0));
+        let num_changes = ConfigHistory::MAX_UNDO_STEPS + 10;
+        for i in 1..=num_changes {
+            history.record(&config(i as f32));
+        }
+
+        let mut num_undos = 0;
+        let mut oldest = None;
+        while let Some(config) = history.undo() {
+            oldest = Some(config.time_scale);
+            num_undos += 1;
+        }
+        assert_eq!(num_undos, ConfigHistory::MAX_UNDO_STEPS);
+        // The oldest changes were forgotten.
+        assert_eq!(
+            oldest,
+            Some((num_changes - ConfigHistory::MAX_UNDO_STEPS) as f32)
+        );
+    }
+}
@@ -0,0 +1,154 @@
+//! Upgrading of config files written by older versions.
+//!
+//! Whenever an existing setting changes its meaning or shape (renamed, different units, different type, ...):
+//! * bump [`CURRENT_VERSION`]
+//! * add a copy of the previous config struct as `ConfigV{n}` to this module
+//! * add a `From<ConfigV{n}> for ConfigV{n + 1}` (or `Config` respectively) doing the conversion
+//! * chain it up in [`migrate`]
+//!
+//! Adding and removing settings does *not* need a new version.
+
+use std::cmp::Ordering;
+
+use super::{Config, ConfigError};
+
+/// Version of configs written by this build.
+pub const CURRENT_VERSION: u32 = 1;
+
+/// Version assumed for config files that don't specify any.
+const UNVERSIONED_CONFIG_VERSION: u32 = 1;
+
+/// Parses a config of any supported version and upgrades it to the current version.
+///
+/// Configs of newer versions are parsed as well as possible and keep their version,
+/// see [`Config::is_from_newer_version`].
+pub fn migrate(ron: &str) -> Result<Config, ConfigError> {
+    let version = read_version(ron)?;
+
+    match version.cmp(&CURRENT_VERSION) {
+        Ordering::Equal => {
+            let mut config: Config = ron::from_str(ron)?;
+            // Unversioned configs.
+            config.version = CURRENT_VERSION;
+            Ok(config)
+        }
+
+        Ordering::Greater => {
+            // Best effort: settings we don't know about are ignored.
+            log::warn!(
+                "Config version {version} is newer than the latest known version {CURRENT_VERSION}. Some settings may be lost."
+            );
+            Ok(ron::from_str(ron)?)
+        }
+
+        // There's no older versions yet, configs without a version are treated as the first version.
+        Ordering::Less => Err(ConfigError::UnsupportedVersion(version)),
+    }
+}
+
+/// Reads only the version of a config, ignoring everything else.
+///
+/// Parses a generic RON value rather than a struct, so it doesn't matter whether the config
+/// is written as `(...)` or with a struct name as `Config(...)`.
+pub fn read_version(ron: &str) -> Result<u32, ConfigError> {
+    let ron::Value::Map(mut fields) = ron::from_str::<ron::Value>(ron)? else {
+        return Err(ConfigError::NotAStruct);
+    };
+
+    match fields.remove(&ron::Value::String("version".to_owned())) {
+        None => Ok(UNVERSIONED_CONFIG_VERSION),
+        Some(ron::Value::Number(version)) => version
+            .as_i64()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or(ConfigError::InvalidVersion),
+        Some(_) => Err(ConfigError::InvalidVersion),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_config_round_trips() {
+        let config = Config::default();
+        assert_eq!(Config::from_ron(&config.to_ron().unwrap()).unwrap(), config);
+    }
+
+    #[test]
+    fn unversioned_config_is_read() {
+        let config = Config::from_ron("(time_scale: 2.0)").unwrap();
+        assert_eq!(config.time_scale, 2.0);
+        assert_eq!(config.version, CURRENT_VERSION);
+    }
+
+    #[test]
+    fn missing_and_unknown_fields_are_tolerated() {
+        let config = Config::from_ron(
+            "(version: 1, screenshot_scale: 3, setting_from_the_future: (a: 1, b: [2, 3]))",
+        )
+        .unwrap();
+        assert_eq!(config.screenshot_scale, 3);
+        assert_eq!(config.time_scale, Config::default().time_scale);
+    }
+
+    #[test]
+    fn named_struct_is_read() {
+        let config = Config::from_ron("Config(version: 1, dithering: false)").unwrap();
+        assert!(!config.dithering);
+    }
+
+    #[test]
+    fn newer_version_is_read_but_keeps_its_version() {
+        let newer_version = CURRENT_VERSION + 1;
+        let config = Config::from_ron(&format!(
+            "(version: {newer_version}, time_scale: 0.5, setting_from_the_future: true)"
+        ))
+        .unwrap();
+        assert_eq!(config.time_scale, 0.5);
+        assert_eq!(config.version, newer_version);
+        assert!(config.is_from_newer_version());
+    }
+
+    #[test]
+    fn older_unsupported_version_is_rejected() {
+        assert!(matches!(
+            Config::from_ron("(version: 0)"),
+            Err(ConfigError::UnsupportedVersion(0))
+        ));
+    }
+
+    #[test]
+    fn malformed_config_is_rejected() {
+        assert!(matches!(
+            Config::from_ron("(version: 1, time_scale: )"),
+            Err(ConfigError::ParseError(_))
+        ));
+        assert!(matches!(
+            Config::from_ron("(time_scale: \"fast\")"),
+            Err(ConfigError::ParseError(_))
+        ));
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn newer_config_file_is_not_overwritten() {
+        let path = std::env::temp_dir().join(format!(
+            "terrain_and_stuff_newer_config_{}.ron",
+            std::process::id()
+        ));
+        let newer_ron = format!(
+            "(version: {}, setting_from_the_future: true)",
+            CURRENT_VERSION + 1
+        );
+        std::fs::write(&path, &newer_ron).unwrap();
+
+        let config = Config::load_from_file(&path).unwrap();
+        let result = config.save_to_file(&path);
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(matches!(result, Err(ConfigError::NewerVersion { .. })));
+        assert_eq!(content, newer_ron);
+    }
+}
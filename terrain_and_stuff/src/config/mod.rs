@@ -0,0 +1,201 @@
+//! Persistent user settings.
+//!
+//! Configs are organized in named [`ConfigProfiles`], each stored as a RON file
+//! (native only, the web always starts with defaults).
+//! Fields missing from a config file fall back to their defaults and unknown fields are ignored,
+//! so adding or removing settings doesn't require a new config version.
+//! Changing the meaning or shape of an existing setting does, see [`migrate`].
+
+mod history;
+mod migrate;
+mod profiles;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::PathBuf;
+
+pub use history::ConfigHistory;
+pub use migrate::CURRENT_VERSION;
+pub use profiles::ConfigProfiles;
+
+#[derive(thiserror::Error, Debug)]
+pub enum ConfigError {
+    #[cfg(not(target_arch = "wasm32"))]
+    #[error("Failed to read config {path:?}: {err}")]
+    FailedToRead { path: PathBuf, err: std::io::Error },
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[error("Failed to write config {path:?}: {err}")]
+    FailedToWrite { path: PathBuf, err: std::io::Error },
+
+    #[error("Failed to parse config: {0}")]
+    ParseError(#[from] ron::error::SpannedError),
+
+    #[error("Failed to serialize config: {0}")]
+    SerializeError(#[from] ron::Error),
+
+    #[error("Config version {0} is not supported")]
+    UnsupportedVersion(u32),
+
+    #[error("Config version must be a non-negative integer")]
+    InvalidVersion,
+
+    #[error("Config must be a struct, e.g. `(version: 1)`")]
+    NotAStruct,
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[error("Not overwriting config {path:?}, it was written by a newer version (config version {version})")]
+    NewerVersion { path: PathBuf, version: u32 },
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[error("Failed to delete config profile {path:?}: {err}")]
+    FailedToDelete { path: PathBuf, err: std::io::Error },
+
+    #[error("Invalid config profile name {0:?}, names may only contain ASCII letters, digits, `_` and `-`")]
+    InvalidProfileName(String),
+
+    #[error("Config profile {0:?} already exists")]
+    ProfileAlreadyExists(String),
+
+    #[error("Config profile {0:?} doesn't exist")]
+    ProfileNotFound(String),
+
+    #[error("Can't delete the last remaining config profile")]
+    CannotDeleteLastProfile,
+}
+
+/// Serializable mirror of [`wgpu::PresentMode`].
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PresentMode {
+    AutoVsync,
+    AutoNoVsync,
+    Fifo,
+    FifoRelaxed,
+    Immediate,
+    Mailbox,
+}
+
+impl From<PresentMode> for wgpu::PresentMode {
+    fn from(present_mode: PresentMode) -> Self {
+        match present_mode {
+            PresentMode::AutoVsync => Self::AutoVsync,
+            PresentMode::AutoNoVsync => Self::AutoNoVsync,
+            PresentMode::Fifo => Self::Fifo,
+            PresentMode::FifoRelaxed => Self::FifoRelaxed,
+            PresentMode::Immediate => Self::Immediate,
+            PresentMode::Mailbox => Self::Mailbox,
+        }
+    }
+}
+
+impl From<wgpu::PresentMode> for PresentMode {
+    fn from(present_mode: wgpu::PresentMode) -> Self {
+        match present_mode {
+            wgpu::PresentMode::AutoVsync => Self::AutoVsync,
+            wgpu::PresentMode::AutoNoVsync => Self::AutoNoVsync,
+            wgpu::PresentMode::Fifo => Self::Fifo,
+            wgpu::PresentMode::FifoRelaxed => Self::FifoRelaxed,
+            wgpu::PresentMode::Immediate => Self::Immediate,
+            wgpu::PresentMode::Mailbox => Self::Mailbox,
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct Config {
+    /// Version of the config format, used for upgrading old config files.
+    version: u32,
+
+    pub present_mode: PresentMode,
+    pub desired_maximum_frame_latency: u32,
+
+    /// Simulation time scale, see [`crate::time::Time`].
+    pub time_scale: f32,
+
+    /// Multiple of the window resolution screenshots are taken at.
+    pub screenshot_scale: u32,
+
+    /// Whether the display output is dithered to hide banding.
+    pub dithering: bool,
+
+    /// Whether the render scale adapts to keep GPU frame times below the target.
+    ///
+    /// Ignored on native adapters without timestamp queries, since fence timings aren't meaningful there.
+    pub dynamic_resolution: bool,
+    pub target_frame_time_ms: f32,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            version: CURRENT_VERSION,
+            present_mode: terrain_renderer::render_output::Screen::DEFAULT_PRESENT_MODE.into(),
+            desired_maximum_frame_latency:
+                terrain_renderer::render_output::Screen::DEFAULT_DESIRED_MAXIMUM_FRAME_LATENCY,
+            time_scale: 1.0,
+            screenshot_scale: 2,
+            dithering: true,
+            dynamic_resolution: false,
+            target_frame_time_ms:
+                terrain_renderer::render_output::DynamicResolution::DEFAULT_TARGET_FRAME_TIME
+                    .as_secs_f32()
+                    * 1000.0,
+        }
+    }
+}
+
+impl Config {
+    /// Parses a config, upgrading it from older versions if necessary.
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))] // Configs aren't persisted on the web.
+    pub fn from_ron(ron: &str) -> Result<Self, ConfigError> {
+        migrate::migrate(ron)
+    }
+
+    /// Whether this config was read from a file written by a newer version.
+    ///
+    /// Such configs may lack settings this version doesn't know about,
+    /// so their files are never overwritten, see [`Config::save_to_file`].
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))] // Configs aren't persisted on the web.
+    pub fn is_from_newer_version(&self) -> bool {
+        self.version > CURRENT_VERSION
+    }
+
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))] // Configs aren't persisted on the web.
+    pub fn to_ron(&self) -> Result<String, ConfigError> {
+        Ok(ron::ser::to_string_pretty(
+            self,
+            ron::ser::PrettyConfig::default(),
+        )?)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load_from_file(path: &std::path::Path) -> Result<Self, ConfigError> {
+        let ron = std::fs::read_to_string(path).map_err(|err| ConfigError::FailedToRead {
+            path: path.to_path_buf(),
+            err,
+        })?;
+        Self::from_ron(&ron)
+    }
+
+    /// Writes the config, unless the existing file was written by a newer version.
+    ///
+    /// Saving would downgrade such a file, losing all settings this version doesn't know about.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save_to_file(&self, path: &std::path::Path) -> Result<(), ConfigError> {
+        if let Some(version) = std::fs::read_to_string(path)
+            .ok()
+            .and_then(|existing| migrate::read_version(&existing).ok())
+            .filter(|version| *version > CURRENT_VERSION)
+        {
+            return Err(ConfigError::NewerVersion {
+                path: path.to_path_buf(),
+                version,
+            });
+        }
+
+        std::fs::write(path, self.to_ron()?).map_err(|err| ConfigError::FailedToWrite {
+            path: path.to_path_buf(),
+            err,
+        })
+    }
+}
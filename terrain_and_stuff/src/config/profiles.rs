@@ -0,0 +1,426 @@
+use std::collections::BTreeMap;
+
+use super::{Config, ConfigError};
+
+/// Named configs of which exactly one is active at a time.
+///
+/// On native, every profile is stored as `configs/<name>.ron` below the asset root
+/// and the name of the active profile is remembered across runs.
+/// Edits of the active profile's file by other programs are picked up while running,
+/// see [`ConfigProfiles::reload_external_edits`].
+/// On the web, profiles only live in memory.
+pub struct ConfigProfiles {
+    profiles: BTreeMap<String, Config>,
+    active: String,
+
+    /// `None` if the configs directory couldn't be watched.
+    #[cfg(not(target_arch = "wasm32"))]
+    config_change_watcher: Option<(
+        notify::RecommendedWatcher,
+        std::sync::mpsc::Receiver<std::path::PathBuf>,
+    )>,
+}
+
+impl ConfigProfiles {
+    pub const DEFAULT_PROFILE_NAME: &'static str = "default";
+
+    /// Loads all profiles, creating a default profile if there is none.
+    pub fn load() -> Self {
+        #[cfg(target_arch = "wasm32")]
+        let (profiles, active) = (BTreeMap::new(), None);
+        #[cfg(not(target_arch = "wasm32"))]
+        let (profiles, active) = (load_profiles_from_disk(), load_active_profile_name());
+
+        let mut profiles = Self {
+            profiles,
+            active: String::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            config_change_watcher: watch_configs_dir(),
+        };
+        if profiles.profiles.is_empty() {
+            profiles
+                .profiles
+                .insert(Self::DEFAULT_PROFILE_NAME.to_owned(), Config::default());
+        }
+
+        profiles.active = active
+            .filter(|name| profiles.profiles.contains_key(name))
+            .or_else(|| {
+                profiles
+                    .profiles
+                    .contains_key(Self::DEFAULT_PROFILE_NAME)
+                    .then(|| Self::DEFAULT_PROFILE_NAME.to_owned())
+            })
+            .unwrap_or_else(|| profiles.profiles.keys().next().unwrap().clone());
+        log::info!("Active config profile: {:?}", profiles.active);
+
+        profiles
+    }
+
+    pub fn active_name(&self) -> &str {
+        &self.active
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.profiles.contains_key(name)
+    }
+
+    pub fn active(&self) -> &Config {
+        &self.profiles[&self.active]
+    }
+
+    /// Replaces the config of the active profile.
+    ///
+    /// The profile is only written to disk on [`ConfigProfiles::save_active`] or when switching profiles.
+    pub fn set_active(&mut self, config: Config) {
+        self.profiles.insert(self.active.clone(), config);
+    }
+
+    /// Name of the profile after the active one, wrapping around at the end.
+    pub fn next_name(&self) -> &str {
+        self.profiles
+            .keys()
+            .skip_while(|name| **name != self.active)
+            .nth(1)
+            .or_else(|| self.profiles.keys().next())
+            .unwrap()
+    }
+
+    /// Saves the active profile and switches to another one.
+    pub fn switch_to(&mut self, name: &str) -> Result<&Config, ConfigError> {
+        if !self.profiles.contains_key(name) {
+            return Err(ConfigError::ProfileNotFound(name.to_owned()));
+        }
+        self.save_active()?;
+        self.active = name.to_owned();
+        self.save_active_name()?;
+
+        Ok(self.active())
+    }
+
+    /// Creates a new profile with the given config and switches to it.
+    pub fn create(&mut self, name: &str, config: Config) -> Result<&Config, ConfigError> {
+        if !is_valid_profile_name(name) {
+            return Err(ConfigError::InvalidProfileName(name.to_owned()));
+        }
+        if self.profiles.contains_key(name) {
+            return Err(ConfigError::ProfileAlreadyExists(name.to_owned()));
+        }
+
+        self.profiles.insert(name.to_owned(), config);
+        self.switch_to(name)
+    }
+
+    /// Creates a new profile with the given config, named after `base_name` plus a number if necessary.
+    pub fn create_with_unique_name(
+        &mut self,
+        base_name: &str,
+        config: Config,
+    ) -> Result<&Config, ConfigError> {
+        let name = std::iter::once(base_name.to_owned())
+            .chain((2..).map(|i| format!("{base_name}_{i}")))
+            .find(|name| !self.profiles.contains_key(name))
+            .unwrap();
+        self.create(&name, config)
+    }
+
+    /// Deletes the active profile and switches to the next one.
+    pub fn delete_active(&mut self) -> Result<&Config, ConfigError> {
+        if self.profiles.len() == 1 {
+            return Err(ConfigError::CannotDeleteLastProfile);
+        }
+
+        let deleted = self.active.clone();
+        let next = self.next_name().to_owned();
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let path = profile_path(&deleted);
+            if path.exists() {
+                std::fs::remove_file(&path)
+                    .map_err(|err| ConfigError::FailedToDelete { path, err })?;
+            }
+        }
+
+        self.profiles.remove(&deleted);
+        self.active = next;
+        self.save_active_name()?;
+
+        Ok(self.active())
+    }
+
+    /// Writes the active profile to disk. Does nothing on the web.
+    pub fn save_active(&self) -> Result<(), ConfigError> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let path = profile_path(&self.active);
+            if let Some(directory) = path.parent() {
+                std::fs::create_dir_all(directory).map_err(|err| ConfigError::FailedToWrite {
+                    path: directory.to_path_buf(),
+                    err,
+                })?;
+            }
+            self.active().save_to_file(&path)?;
+        }
+
+        Ok(())
+    }
+
+    /// Reloads the active profile if its file was changed by another program, e.g. an editor or a script.
+    ///
+    /// Returns the live config with all settings that changed in the file applied,
+    /// settings that weren't touched in the file keep their live values.
+    /// Changes written by [`ConfigProfiles::save_active`] itself are ignored.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn reload_external_edits(&mut self, live: &Config) -> Option<Config> {
+        let (_, change_rx) = self.config_change_watcher.as_ref()?;
+        let path = profile_path(&self.active);
+        let num_active_profile_changes = change_rx
+            .try_iter()
+            .filter(|changed_path| changed_path.file_name() == path.file_name())
+            .count();
+        if num_active_profile_changes == 0 {
+            return None;
+        }
+
+        let edited = match Config::load_from_file(&path) {
+            Ok(edited) => edited,
+            Err(err) => {
+                // Might be a partial write, the next change event will try again.
+                log::error!(
+                    "{err}\nIgnoring external edit of config profile {:?}.",
+                    self.active
+                );
+                return None;
+            }
+        };
+        if edited == *self.active() {
+            return None;
+        }
+
+        let merged = match merge_edits(self.active(), &edited, live) {
+            Ok(merged) => merged,
+            Err(err) => {
+                log::error!(
+                    "Failed to merge external edit of config profile {:?}: {err}",
+                    self.active
+                );
+                return None;
+            }
+        };
+        self.profiles.insert(self.active.clone(), edited);
+
+        Some(merged)
+    }
+
+    fn save_active_name(&self) -> Result<(), ConfigError> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let path = configs_dir().join(ACTIVE_PROFILE_FILE_NAME);
+            std::fs::write(&path, &self.active)
+                .map_err(|err| ConfigError::FailedToWrite { path, err })?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Profile names are used as file names, so they're restricted to characters that are safe on every platform.
+fn is_valid_profile_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// File in the configs directory that holds the name of the active profile.
+#[cfg(not(target_arch = "wasm32"))]
+const ACTIVE_PROFILE_FILE_NAME: &str = "active_profile.txt";
+
+#[cfg(not(target_arch = "wasm32"))]
+fn configs_dir() -> std::path::PathBuf {
+    terrain_renderer::assets::asset_root().join("configs")
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn profile_path(name: &str) -> std::path::PathBuf {
+    configs_dir().join(format!("{name}.ron"))
+}
+
+/// Watches the configs directory, creating it if necessary.
+#[cfg(not(target_arch = "wasm32"))]
+fn watch_configs_dir() -> Option<(
+    notify::RecommendedWatcher,
+    std::sync::mpsc::Receiver<std::path::PathBuf>,
+)> {
+    let directory = configs_dir();
+    let result = std::fs::create_dir_all(&directory)
+        .map_err(notify::Error::io)
+        .and_then(|()| terrain_renderer::resource_managers::watch_directory(&directory, "config"));
+
+    match result {
+        Ok(watcher) => Some(watcher),
+        Err(err) => {
+            log::warn!("Failed to watch configs directory {directory:?}, external edits won't be reloaded: {err}");
+            None
+        }
+    }
+}
+
+/// Applies all settings that differ between `base` & `edited` to `live`.
+///
+/// Works on the RON representation, which has one line per setting.
+#[cfg(not(target_arch = "wasm32"))]
+fn merge_edits(base: &Config, edited: &Config, live: &Config) -> Result<Config, ConfigError> {
+    let (base, edited, live) = (base.to_ron()?, edited.to_ron()?, live.to_ron()?);
+    let merged = base
+        .lines()
+        .zip(edited.lines())
+        .zip(live.lines())
+        .map(|((base, edited), live)| if base != edited { edited } else { live })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    Config::from_ron(&merged)
+}
+
+/// Config file used before there were profiles, relative to the asset root.
+#[cfg(not(target_arch = "wasm32"))]
+const LEGACY_CONFIG_FILE_NAME: &str = "config.ron";
+
+/// Imports the config from before there were profiles as the default profile.
+///
+/// Only done if there is no configs directory yet. The imported profile is saved right away,
+/// so the import happens only once. The legacy config file is left untouched.
+#[cfg(not(target_arch = "wasm32"))]
+fn import_legacy_config() -> BTreeMap<String, Config> {
+    let legacy_path = terrain_renderer::assets::asset_root().join(LEGACY_CONFIG_FILE_NAME);
+    if !legacy_path.exists() {
+        log::info!(
+            "No configs directory at {:?}, using defaults.",
+            configs_dir()
+        );
+        return BTreeMap::new();
+    }
+
+    let config = match Config::load_from_file(&legacy_path) {
+        Ok(config) => config,
+        Err(err) => {
+            log::error!("{err}\nNot importing legacy config {legacy_path:?}, using defaults.");
+            return BTreeMap::new();
+        }
+    };
+
+    let name = ConfigProfiles::DEFAULT_PROFILE_NAME;
+    let path = profile_path(name);
+    let result = std::fs::create_dir_all(configs_dir())
+        .map_err(|err| ConfigError::FailedToWrite {
+            path: configs_dir(),
+            err,
+        })
+        .and_then(|()| config.save_to_file(&path));
+    match result {
+        Ok(()) => log::info!("Imported legacy config {legacy_path:?} as config profile {name:?}"),
+        Err(err) => log::error!("Failed to save imported legacy config: {err}"),
+    }
+
+    BTreeMap::from([(name.to_owned(), config)])
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn load_active_profile_name() -> Option<String> {
+    std::fs::read_to_string(configs_dir().join(ACTIVE_PROFILE_FILE_NAME))
+        .ok()
+        .map(|name| name.trim().to_owned())
+}
+
+/// Loads all profiles from the configs directory.
+///
+/// Profiles that fail to load are backed up and replaced with the default config,
+/// so they're not lost when the profile is saved next time.
+#[cfg(not(target_arch = "wasm32"))]
+fn load_profiles_from_disk() -> BTreeMap<String, Config> {
+    let directory = configs_dir();
+    let Ok(entries) = std::fs::read_dir(&directory) else {
+        return import_legacy_config();
+    };
+
+    entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            if path.extension()? != "ron" {
+                return None;
+            }
+            let name = path.file_stem()?.to_str()?.to_owned();
+
+            let config = Config::load_from_file(&path).unwrap_or_else(|err| {
+                let backup_path = path.with_extension("ron.bak");
+                log::error!(
+                    "{err}\nUsing default config for profile {name:?}, backing up the old config to {backup_path:?}."
+                );
+                if let Err(err) = std::fs::copy(&path, &backup_path) {
+                    log::error!("Failed to back up config: {err}");
+                }
+                Config::default()
+            });
+            if config.is_from_newer_version() {
+                log::warn!(
+                    "Config profile {name:?} was written by a newer version, changes to it won't be saved."
+                );
+            }
+
+            Some((name, config))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profile_names_are_restricted_to_safe_characters() {
+        for name in ["default", "noon-profiling", "sunset_2", "A1"] {
+            assert!(is_valid_profile_name(name), "{name:?}");
+        }
+        for name in [
+            "",
+            "my profile",
+            "../escape",
+            ".hidden",
+            "a/b",
+            "a\\b",
+            "c:",
+            "ümlaut",
+        ] {
+            assert!(!is_valid_profile_name(name), "{name:?}");
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn external_edits_are_merged_into_live_config() {
+        let base = Config::default();
+        let edited = Config {
+            time_scale: 2.0,
+            dithering: !base.dithering,
+            ..base.clone()
+        };
+        let live = Config {
+            time_scale: 0.5,
+            screenshot_scale: 4,
+            ..base.clone()
+        };
+
+        let merged = merge_edits(&base, &edited, &live).unwrap();
+        assert_eq!(
+            merged,
+            Config {
+                time_scale: 2.0,
+                dithering: !base.dithering,
+                screenshot_scale: 4,
+                ..base
+            }
+        );
+    }
+}
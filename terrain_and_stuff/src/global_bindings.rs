@@ -0,0 +1,99 @@
+//! Bindings shared by all scene passes at bind group 0.
+
+use crate::{
+    shader_interop::wgsl_struct,
+    wgpu_utils::{BindGroupBuilder, BindGroupLayoutBuilder, BindGroupLayoutWithDesc},
+};
+
+wgsl_struct! {
+    /// Per-frame constants.
+    ///
+    /// Imported by `global_bindings.wgsl` from the generated shader file.
+    pub struct FrameUniformBuffer {
+        /// Resolution of the render targets in pixels.
+        pub resolution: [f32; 2],
+        /// Reciprocal of the resolution, i.e. the size of a pixel in UV coordinates.
+        pub inv_resolution: [f32; 2],
+
+        /// Subpixel offset of the projection in pixels for temporal anti-aliasing.
+        ///
+        /// Always zero until there's TAA.
+        pub jitter_offset: [f32; 2],
+
+        /// Index of the frame, incremented by one each frame.
+        pub frame_index: u32,
+
+        /// Simulation time in seconds, can be paused, scaled and scrubbed.
+        pub elapsed_time: f32,
+
+        /// Simulation time passed since the last frame in seconds.
+        pub delta_time: f32,
+
+        /// Real time passed since the last frame in seconds.
+        pub real_delta_time: f32,
+    }
+}
+
+/// Bind group that is shared by all scene rendering passes, always bound at group 0.
+///
+/// Shaders import the bindings from `global_bindings.wgsl`.
+pub struct GlobalBindings {
+    bind_group_layout: BindGroupLayoutWithDesc,
+    bind_group: wgpu::BindGroup,
+    frame_uniform_buffer: wgpu::Buffer,
+}
+
+impl GlobalBindings {
+    /// Creates the bind group along with its uniform buffer, which is zeroed until the first update.
+    pub fn new(device: &wgpu::Device) -> Self {
+        let bind_group_layout = BindGroupLayoutBuilder::new()
+            .next_binding_all(wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: std::num::NonZeroU64::new(
+                    std::mem::size_of::<FrameUniformBuffer>() as u64,
+                ),
+            })
+            .create(device, "Global bindings");
+
+        let frame_uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("FrameUniformBuffer"),
+            size: std::mem::size_of::<FrameUniformBuffer>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group = BindGroupBuilder::new(&bind_group_layout)
+            .buffer(frame_uniform_buffer.as_entire_buffer_binding())
+            .create(device, "Global bindings");
+
+        Self {
+            bind_group_layout,
+            bind_group,
+            frame_uniform_buffer,
+        }
+    }
+
+    /// Layout of group 0 that all scene pipelines need to start with.
+    pub fn bind_group_layout(&self) -> &BindGroupLayoutWithDesc {
+        &self.bind_group_layout
+    }
+
+    /// Bind group to set at group 0 before drawing.
+    pub fn bind_group(&self) -> &wgpu::BindGroup {
+        &self.bind_group
+    }
+
+    /// Uploads the constants for the next frame, takes effect with the next queue submission.
+    pub fn update_frame_uniform_buffer(
+        &self,
+        queue: &wgpu::Queue,
+        frame_uniform_buffer: &FrameUniformBuffer,
+    ) {
+        queue.write_buffer(
+            &self.frame_uniform_buffer,
+            0,
+            bytemuck::bytes_of(frame_uniform_buffer),
+        );
+    }
+}
@@ -0,0 +1,239 @@
+//! Per-frame input with recording & deterministic replay.
+//!
+//! All input the application reacts to goes through [`Input`], so a recorded session can be replayed
+//! frame by frame, including window size changes and frame durations.
+//! Recordings are streamed to disk one frame per line, so they survive crashes.
+
+use std::{
+    io::{BufRead as _, Write as _},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use minifb::{Key, KeyRepeat, Window};
+
+use crate::key_bindings::{Action, KeyBinding, KeyBindings};
+
+#[derive(thiserror::Error, Debug)]
+pub enum InputRecordingError {
+    #[error("Failed to access input recording {path:?}: {err}")]
+    Io { path: PathBuf, err: std::io::Error },
+
+    #[error("Failed to parse frame {frame} of input recording {path:?}: {err}")]
+    InvalidFrame {
+        path: PathBuf,
+        frame: usize,
+        err: ron::error::SpannedError,
+    },
+
+    #[error(transparent)]
+    Serialize(#[from] ron::Error),
+}
+
+/// Input state of a single frame.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Default)]
+pub struct FrameInput {
+    /// Debug names of all keys that are held down.
+    ///
+    /// `minifb::Key` isn't serializable, so keys are identified by name.
+    keys_down: Vec<String>,
+
+    /// Debug names of all keys that were pressed this frame.
+    keys_pressed: Vec<String>,
+
+    window_size: (usize, usize),
+
+    /// Real time that passed since the previous frame.
+    real_delta: Duration,
+}
+
+impl FrameInput {
+    fn from_window(window: &Window, real_delta: Duration) -> Self {
+        Self {
+            keys_down: window.get_keys().iter().map(key_name).collect(),
+            keys_pressed: window
+                .get_keys_pressed(KeyRepeat::No)
+                .iter()
+                .map(key_name)
+                .collect(),
+            window_size: window.get_size(),
+            real_delta,
+        }
+    }
+}
+
+fn key_name(key: &Key) -> String {
+    format!("{key:?}")
+}
+
+enum InputSource {
+    Window,
+    Recording {
+        path: PathBuf,
+        file: std::fs::File,
+    },
+    Replay {
+        frames: std::vec::IntoIter<FrameInput>,
+    },
+}
+
+pub struct Input {
+    source: InputSource,
+    frame: FrameInput,
+    replay_finished: bool,
+    key_bindings: KeyBindings,
+}
+
+impl Input {
+    /// Reads input from the window.
+    pub fn from_window() -> Self {
+        Self {
+            source: InputSource::Window,
+            frame: FrameInput::default(),
+            replay_finished: false,
+            key_bindings: KeyBindings::default(),
+        }
+    }
+
+    /// Reads input from the window and records it to a file.
+    pub fn record(path: &Path) -> Result<Self, InputRecordingError> {
+        let file = std::fs::File::create(path).map_err(|err| InputRecordingError::Io {
+            path: path.to_path_buf(),
+            err,
+        })?;
+        log::info!("Recording input to {path:?}");
+
+        Ok(Self {
+            source: InputSource::Recording {
+                path: path.to_path_buf(),
+                file,
+            },
+            frame: FrameInput::default(),
+            replay_finished: false,
+            key_bindings: KeyBindings::default(),
+        })
+    }
+
+    /// Replays previously recorded input, ignoring the window.
+    pub fn replay(path: &Path) -> Result<Self, InputRecordingError> {
+        let io_error = |err| InputRecordingError::Io {
+            path: path.to_path_buf(),
+            err,
+        };
+        let file = std::fs::File::open(path).map_err(io_error)?;
+
+        let mut frames = Vec::new();
+        for (frame, line) in std::io::BufReader::new(file).lines().enumerate() {
+            let line = line.map_err(io_error)?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            frames.push(
+                ron::from_str(&line).map_err(|err| InputRecordingError::InvalidFrame {
+                    path: path.to_path_buf(),
+                    frame,
+                    err,
+                })?,
+            );
+        }
+        log::info!("Replaying {} frames of input from {path:?}", frames.len());
+
+        Ok(Self {
+            source: InputSource::Replay {
+                frames: frames.into_iter(),
+            },
+            frame: FrameInput::default(),
+            replay_finished: false,
+            key_bindings: KeyBindings::default(),
+        })
+    }
+
+    /// Gathers the input for a new frame. Should be called exactly once at the start of every frame.
+    ///
+    /// `real_delta` is the measured time since the last frame, which is overridden by replays.
+    pub fn begin_frame(&mut self, window: &Window, real_delta: Duration) {
+        match &mut self.source {
+            InputSource::Window => {
+                self.frame = FrameInput::from_window(window, real_delta);
+            }
+
+            InputSource::Recording { path, file } => {
+                self.frame = FrameInput::from_window(window, real_delta);
+
+                let result = ron::to_string(&self.frame)
+                    .map_err(InputRecordingError::from)
+                    .and_then(|line| {
+                        writeln!(file, "{line}").map_err(|err| InputRecordingError::Io {
+                            path: path.clone(),
+                            err,
+                        })
+                    });
+                if let Err(err) = result {
+                    log::error!("{err}\nStopping input recording.");
+                    self.source = InputSource::Window;
+                }
+            }
+
+            InputSource::Replay { frames } => {
+                if let Some(frame) = frames.next() {
+                    self.frame = frame;
+                } else {
+                    if !self.replay_finished {
+                        log::info!("Input replay finished.");
+                    }
+                    self.replay_finished = true;
+                    self.frame = FrameInput {
+                        window_size: self.frame.window_size,
+                        ..FrameInput::from_window(window, real_delta)
+                    };
+                }
+            }
+        }
+    }
+
+    /// Whether a replay ran out of recorded frames.
+    pub fn is_replay_finished(&self) -> bool {
+        self.replay_finished
+    }
+
+    pub fn key_bindings(&self) -> &KeyBindings {
+        &self.key_bindings
+    }
+
+    /// Whether the key bound to an action was pressed this frame.
+    pub fn is_action_pressed(&self, action: Action) -> bool {
+        self.key_bindings.binding(action).is_some_and(|binding| {
+            self.matches_modifiers(binding) && self.is_key_pressed(binding.key)
+        })
+    }
+
+    /// Whether the key bound to an action is held down.
+    pub fn is_action_down(&self, action: Action) -> bool {
+        self.key_bindings
+            .binding(action)
+            .is_some_and(|binding| self.matches_modifiers(binding) && self.is_key_down(binding.key))
+    }
+
+    fn matches_modifiers(&self, binding: KeyBinding) -> bool {
+        let ctrl_down = self.is_key_down(Key::LeftCtrl) || self.is_key_down(Key::RightCtrl);
+        ctrl_down == binding.ctrl
+    }
+
+    fn is_key_down(&self, key: Key) -> bool {
+        let name = key_name(&key);
+        self.frame.keys_down.contains(&name)
+    }
+
+    fn is_key_pressed(&self, key: Key) -> bool {
+        let name = key_name(&key);
+        self.frame.keys_pressed.contains(&name)
+    }
+
+    pub fn window_size(&self) -> (usize, usize) {
+        self.frame.window_size
+    }
+
+    pub fn real_delta(&self) -> Duration {
+        self.frame.real_delta
+    }
+}
@@ -0,0 +1,185 @@
+//! Mapping of keys to the actions they trigger.
+//!
+//! All keyboard controls go through [`KeyBindings`], so the help can list whatever is currently bound.
+
+use minifb::{Key, KeyRepeat, Window};
+
+/// Everything that can be triggered via keyboard.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Action {
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))] // Can't exit on the web.
+    Exit,
+    ShowHelp,
+
+    CyclePresentMode,
+    CycleFrameLatency,
+    ToggleDynamicResolution,
+    ToggleDithering,
+
+    TogglePause,
+    SlowDown,
+    SpeedUp,
+    ScrubBackward,
+    ScrubForward,
+
+    NextConfigProfile,
+    NewConfigProfile,
+    DuplicateConfigProfile,
+    DeleteConfigProfile,
+    Undo,
+    Redo,
+    LogConfigHistory,
+
+    LogErrorHistory,
+    CycleWireframeMode,
+    ToggleSubmissionTimings,
+    ToggleSplitSubmissions,
+    CaptureFrame,
+    DumpFrame,
+    LogCompileTimes,
+
+    CycleScreenshotScale,
+    TakeScreenshot,
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))] // Native only.
+    ToggleVideoRecording,
+}
+
+impl Action {
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::Exit => "Exit",
+            Self::ShowHelp => "Show this help",
+            Self::CyclePresentMode => "Cycle present mode",
+            Self::CycleFrameLatency => "Cycle desired maximum frame latency",
+            Self::ToggleDynamicResolution => "Toggle dynamic resolution",
+            Self::ToggleDithering => "Toggle dithering of the display output",
+            Self::TogglePause => "Pause/resume simulation",
+            Self::SlowDown => "Halve simulation speed",
+            Self::SpeedUp => "Double simulation speed",
+            Self::ScrubBackward => "Scrub simulation time backward (hold)",
+            Self::ScrubForward => "Scrub simulation time forward (hold)",
+            Self::NextConfigProfile => "Switch to next config profile",
+            Self::NewConfigProfile => "Create config profile with default settings",
+            Self::DuplicateConfigProfile => "Duplicate active config profile",
+            Self::DeleteConfigProfile => "Delete active config profile (press twice to confirm)",
+            Self::Undo => "Undo settings change",
+            Self::Redo => "Redo settings change",
+            Self::LogConfigHistory => "Log settings change history",
+            Self::LogErrorHistory => "Log wgpu error history",
+            Self::CycleWireframeMode => "Cycle wireframe mode",
+            Self::ToggleSubmissionTimings => {
+                "Toggle logging of GPU & CPU timings and pipeline statistics"
+            }
+            Self::ToggleSplitSubmissions => {
+                "Toggle submitting each frame as one or several command buffers"
+            }
+            Self::CaptureFrame => "Capture frame in graphics debugger",
+            Self::DumpFrame => "Dump intermediate render targets of the next frame to disk",
+            Self::LogCompileTimes => "Log shader compile & pipeline creation times",
+            Self::CycleScreenshotScale => "Cycle screenshot scale",
+            Self::TakeScreenshot => "Take screenshot",
+            Self::ToggleVideoRecording => "Start/stop video recording",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct KeyBinding {
+    pub key: Key,
+
+    /// Whether either control key needs to be held.
+    ///
+    /// Bindings without control don't trigger while control is held.
+    pub ctrl: bool,
+}
+
+impl KeyBinding {
+    pub const fn key(key: Key) -> Self {
+        Self { key, ctrl: false }
+    }
+
+    pub const fn ctrl(key: Key) -> Self {
+        Self { key, ctrl: true }
+    }
+
+    /// Checks the binding directly against the window, bypassing input recording & replay.
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))] // Can't exit on the web.
+    pub fn is_pressed_in_window(&self, window: &Window) -> bool {
+        let ctrl_down = window.is_key_down(Key::LeftCtrl) || window.is_key_down(Key::RightCtrl);
+        ctrl_down == self.ctrl && window.is_key_pressed(self.key, KeyRepeat::No)
+    }
+}
+
+impl std::fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let key = match self.key {
+            Key::Minus => "-".to_owned(),
+            Key::Equal => "=".to_owned(),
+            Key::Comma => ",".to_owned(),
+            Key::Period => ".".to_owned(),
+            key => format!("{key:?}"),
+        };
+        if self.ctrl {
+            write!(f, "Ctrl+{key}")
+        } else {
+            f.write_str(&key)
+        }
+    }
+}
+
+/// Which key triggers which action.
+pub struct KeyBindings {
+    bindings: Vec<(Action, KeyBinding)>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        let bindings = vec![
+            #[cfg(not(target_arch = "wasm32"))]
+            (Action::Exit, KeyBinding::key(Key::Escape)),
+            (Action::ShowHelp, KeyBinding::key(Key::F1)),
+            (Action::CyclePresentMode, KeyBinding::key(Key::V)),
+            (Action::CycleFrameLatency, KeyBinding::key(Key::L)),
+            (Action::ToggleDynamicResolution, KeyBinding::key(Key::R)),
+            (Action::ToggleDithering, KeyBinding::key(Key::D)),
+            (Action::TogglePause, KeyBinding::key(Key::P)),
+            (Action::SlowDown, KeyBinding::key(Key::Minus)),
+            (Action::SpeedUp, KeyBinding::key(Key::Equal)),
+            (Action::ScrubBackward, KeyBinding::key(Key::Comma)),
+            (Action::ScrubForward, KeyBinding::key(Key::Period)),
+            (Action::NextConfigProfile, KeyBinding::key(Key::F3)),
+            (Action::NewConfigProfile, KeyBinding::key(Key::F4)),
+            (Action::DuplicateConfigProfile, KeyBinding::key(Key::F5)),
+            (Action::DeleteConfigProfile, KeyBinding::key(Key::F6)),
+            (Action::Undo, KeyBinding::ctrl(Key::Z)),
+            (Action::Redo, KeyBinding::ctrl(Key::Y)),
+            (Action::LogConfigHistory, KeyBinding::ctrl(Key::H)),
+            (Action::LogErrorHistory, KeyBinding::key(Key::F2)),
+            (Action::CycleWireframeMode, KeyBinding::key(Key::F7)),
+            (Action::ToggleSubmissionTimings, KeyBinding::key(Key::F11)),
+            (Action::ToggleSplitSubmissions, KeyBinding::ctrl(Key::F11)),
+            (Action::CaptureFrame, KeyBinding::key(Key::F12)),
+            (Action::DumpFrame, KeyBinding::ctrl(Key::D)),
+            (Action::LogCompileTimes, KeyBinding::ctrl(Key::T)),
+            (Action::CycleScreenshotScale, KeyBinding::key(Key::F9)),
+            (Action::TakeScreenshot, KeyBinding::key(Key::F10)),
+            #[cfg(not(target_arch = "wasm32"))]
+            (Action::ToggleVideoRecording, KeyBinding::key(Key::F8)),
+        ];
+        Self { bindings }
+    }
+}
+
+impl KeyBindings {
+    pub fn binding(&self, action: Action) -> Option<KeyBinding> {
+        self.bindings
+            .iter()
+            .find(|(bound_action, _)| *bound_action == action)
+            .map(|(_, binding)| *binding)
+    }
+
+    /// All bound actions in the order they should be presented.
+    pub fn iter(&self) -> impl Iterator<Item = (Action, KeyBinding)> + '_ {
+        self.bindings.iter().copied()
+    }
+}
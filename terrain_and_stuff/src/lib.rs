@@ -0,0 +1,29 @@
+//! Renderer core of terrain_and_stuff, usable independently of the demo application.
+//!
+//! Contains everything needed to render with your own windowing & event loop:
+//! * [`resource_managers`]: pipelines & textures with hot reload on native, shader composition via naga_oil
+//! * [`wgpu_utils`]: bind group helpers, readback, timing & debug utilities on top of wgpu
+//! * [`wgpu_error_handling`]: tracking of wgpu errors across frames
+//! * [`render_output`]: HDR backbuffer, display transform & the surface it's presented to
+//! * [`global_bindings`], [`sky`], [`triangle`] & [`wireframe`]: the scene passes
+//!
+//! Shaders & assets are loaded relative to [`assets::asset_root`] on native and embedded on the web.
+//! The demo application in `main.rs` (window, input, config, CLI) is the reference consumer of this library.
+
+#![warn(missing_docs)]
+
+#[cfg(target_arch = "wasm32")]
+mod assets_embedded;
+#[cfg(target_arch = "wasm32")]
+mod shaders_embedded;
+
+pub mod assets;
+pub mod global_bindings;
+pub mod render_output;
+pub mod resource_managers;
+pub mod shader_interop;
+pub mod sky;
+pub mod triangle;
+pub mod wgpu_error_handling;
+pub mod wgpu_utils;
+pub mod wireframe;
@@ -1,47 +1,147 @@
 #[cfg(not(target_arch = "wasm32"))]
+mod benchmark;
+#[cfg(not(target_arch = "wasm32"))]
+mod cli;
+#[cfg(not(target_arch = "wasm32"))]
 mod main_desktop;
 #[cfg(target_arch = "wasm32")]
 mod main_web;
-#[cfg(target_arch = "wasm32")]
-mod shaders_embedded;
+#[cfg(not(target_arch = "wasm32"))]
+mod video_recorder;
 
-mod render_output;
-mod resource_managers;
-mod sky;
-mod wgpu_error_handling;
-mod wgpu_utils;
+mod config;
+mod input;
+mod key_bindings;
+mod time;
 
 // -----------------------------------------
 
 use std::sync::{atomic::AtomicU64, Arc};
 
 use anyhow::Context;
+use config::{Config, ConfigHistory, ConfigProfiles};
+use input::Input;
+use key_bindings::Action;
 use minifb::{Window, WindowOptions};
-use render_output::{HdrBackbuffer, Screen};
-use resource_managers::{
-    PipelineManager, RenderPipelineDescriptor, RenderPipelineHandle, ShaderEntryPoint,
+use terrain_renderer::{
+    global_bindings::{FrameUniformBuffer, GlobalBindings},
+    render_output::{screenshot, DebugDump, DynamicResolution, HdrBackbuffer, Screen},
+    resource_managers::{PipelineManager, RenderPipelineHandle, TextureManager},
+    sky::Sky,
+    triangle,
+    wgpu_error_handling::{ErrorTracker, WgpuErrorScope},
+    wgpu_utils::{
+        DebugGroup, FrameCapture, GpuCapabilities, GpuTimer, PipelineStatistics, ReadbackBelt,
+        SubmissionTimer,
+    },
+    wireframe::{self, WireframeMode},
 };
-use sky::Sky;
-use wgpu_error_handling::{ErrorTracker, WgpuErrorScope};
+use time::Time;
+
+const WINDOW_TITLE: &str = "terrain_and_stuff";
+
+/// Time within which deleting a config profile has to be confirmed by pressing the binding again.
+const PROFILE_DELETION_CONFIRMATION_TIMEOUT: web_time::Duration = web_time::Duration::from_secs(3);
+pub const WIDTH: usize = 1920;
+pub const HEIGHT: usize = 1080;
+
+/// Settings the application is started with.
+///
+/// On native, these can be set via the command line.
+pub struct StartupOptions {
+    pub window_width: usize,
+    pub window_height: usize,
 
-const WIDTH: usize = 1920;
-const HEIGHT: usize = 1080;
+    /// Config profile to activate, created with default settings if it doesn't exist yet.
+    pub profile: Option<String>,
+
+    /// Config to start with instead of the one from the active profile.
+    ///
+    /// Isn't written back to the active profile on exit.
+    pub config: Option<Config>,
+
+    /// File to record all input to.
+    pub record_input: Option<std::path::PathBuf>,
+
+    /// File to replay recorded input from instead of reading input from the window.
+    pub replay_input: Option<std::path::PathBuf>,
+
+    /// Settings for all videos recorded in this session.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub video_settings: video_recorder::VideoSettings,
+
+    /// File to record a video to right from the start.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub record_video: Option<std::path::PathBuf>,
+}
+
+impl Default for StartupOptions {
+    fn default() -> Self {
+        Self {
+            window_width: WIDTH,
+            window_height: HEIGHT,
+            profile: None,
+            config: None,
+            record_input: None,
+            replay_input: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            video_settings: Default::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            record_video: None,
+        }
+    }
+}
 
 struct Application<'a> {
     screen: Screen<'a>,
     hdr_backbuffer: HdrBackbuffer,
     sky: Sky,
+    time: Time,
+    global_bindings: GlobalBindings,
 
     window: Window,
+    input: Input,
     adapter: wgpu::Adapter,
+    capabilities: GpuCapabilities,
     device: Arc<wgpu::Device>,
     queue: wgpu::Queue,
 
     active_frame_index: u64,
     frame_index_for_uncaptured_errors: Arc<AtomicU64>,
     pipeline_manager: PipelineManager,
+    texture_manager: TextureManager,
+    readback_belt: ReadbackBelt,
+    frame_capture: FrameCapture,
+    submission_timer: SubmissionTimer,
+    gpu_timer: GpuTimer,
+    pipeline_statistics: PipelineStatistics,
+    /// Submit scene & output in separate command buffers instead of a single one per frame.
+    split_submissions: bool,
+    dynamic_resolution: DynamicResolution,
     triangle_render_pipeline: RenderPipelineHandle,
+    triangle_wireframe_pipeline: Option<RenderPipelineHandle>,
+    wireframe_mode: WireframeMode,
+
+    /// Multiple of the window resolution screenshots are taken at.
+    screenshot_scale: u32,
+    screenshot_requested: bool,
+    debug_dump_requested: bool,
+
+    #[cfg(not(target_arch = "wasm32"))]
+    video_recorder: video_recorder::VideoRecorder,
+
     error_tracker: Arc<ErrorTracker>,
+    config_profiles: ConfigProfiles,
+    config_history: ConfigHistory,
+
+    /// When deleting the active config profile was first requested, waiting for confirmation.
+    profile_deletion_requested: Option<web_time::Instant>,
+
+    /// Whether settings are saved to the active config profile on exit.
+    persist_config: bool,
+
+    /// Error count last shown in the window title.
+    displayed_error_count: u64,
 }
 
 impl<'a> Application<'a> {
@@ -49,15 +149,15 @@ impl<'a> Application<'a> {
     ///
     /// There's various ways for this to fail, all of which are handled via `expect` right now.
     /// Of course there's be better ways to handle these (e.g. show something nice on screen or try a bit harder).
-    async fn new() -> anyhow::Result<Self> {
+    async fn new(options: StartupOptions) -> anyhow::Result<Self> {
         let instance =
             wgpu::util::new_instance_with_webgpu_detection(wgpu::InstanceDescriptor::default())
                 .await;
 
         let window = Window::new(
-            "terrain_and_stuff",
-            WIDTH,
-            HEIGHT,
+            WINDOW_TITLE,
+            options.window_width,
+            options.window_height,
             WindowOptions {
                 resize: true,
                 ..Default::default()
@@ -91,12 +191,16 @@ impl<'a> Application<'a> {
             .request_device(
                 &wgpu::DeviceDescriptor {
                     label: Some("Device"),
+                    // All features are optional, subsystems fall back to something else if they're missing.
+                    required_features: GpuCapabilities::features_to_request(&adapter),
                     ..Default::default()
                 },
                 None,
             )
             .await
             .context("Failed to create device")?;
+        let capabilities = GpuCapabilities::from_device(&device);
+        log::info!("GPU capabilities:\n{capabilities}");
 
         // Make all errors forward to the console before panicking, this way they also show up on the web!
         let error_tracker = Arc::new(ErrorTracker::default());
@@ -121,108 +225,688 @@ impl<'a> Application<'a> {
             })
         });
 
+        let input = if let Some(path) = &options.replay_input {
+            Input::replay(path)?
+        } else if let Some(path) = &options.record_input {
+            Input::record(path)?
+        } else {
+            Input::from_window()
+        };
+
         let mut pipeline_manager = PipelineManager::new().context("Create pipeline manager")?;
+        let texture_manager =
+            TextureManager::new(capabilities).context("Create texture manager")?;
 
         let resolution = glam::uvec2(window.get_size().0 as _, window.get_size().1 as _);
         let screen = Screen::new(&device, &adapter, surface, resolution);
         let hdr_backbuffer = HdrBackbuffer::new(
             &device,
-            resolution,
+            screen.resolution(),
             &mut pipeline_manager,
             screen.surface_format(),
         )
         .context("Create HDR backbuffer & display transform pipeline")?;
-        let sky = Sky::new(&device, &mut pipeline_manager).context("Create sky renderer")?;
+        let global_bindings = GlobalBindings::new(&device);
+        let sky = Sky::new(&device, &mut pipeline_manager, &global_bindings)
+            .context("Create sky renderer")?;
 
         let triangle_render_pipeline =
-            Self::create_triangle_render_pipeline(&mut pipeline_manager, &device);
+            triangle::create_render_pipeline(&device, &mut pipeline_manager, &global_bindings)
+                .context("Create triangle pipeline")?;
+        let triangle_wireframe_pipeline = wireframe::create_wireframe_variant(
+            &device,
+            &capabilities,
+            &mut pipeline_manager,
+            triangle_render_pipeline,
+        )
+        .context("Create triangle wireframe pipeline")?;
+        let pipeline_statistics = PipelineStatistics::new(&device, &capabilities);
+        let gpu_timer = GpuTimer::new(&device, &queue, &capabilities);
+        // Fence based submission timings are only a fallback, timestamp queries are far more precise.
+        let submission_timer = SubmissionTimer::new(!gpu_timer.is_supported());
 
-        Ok(Application {
+        let mut application = Application {
             sky,
             screen,
             hdr_backbuffer,
+            time: Time::new(),
+            global_bindings,
 
             window,
+            input,
             adapter,
+            capabilities,
             device: Arc::new(device),
             queue,
 
             active_frame_index: 0,
             error_tracker,
+            displayed_error_count: 0,
             frame_index_for_uncaptured_errors,
             pipeline_manager,
+            texture_manager,
+            readback_belt: ReadbackBelt::new(),
+            submission_timer,
+            gpu_timer,
+            pipeline_statistics,
+            split_submissions: false,
+            dynamic_resolution: DynamicResolution::new(),
+            frame_capture: FrameCapture::new(),
             triangle_render_pipeline,
-        })
+            triangle_wireframe_pipeline,
+            wireframe_mode: WireframeMode::default(),
+            screenshot_scale: 1,
+            screenshot_requested: false,
+            debug_dump_requested: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            video_recorder: video_recorder::VideoRecorder::new(options.video_settings),
+            config_profiles: ConfigProfiles::load(),
+            config_history: ConfigHistory::new(Config::default()),
+            profile_deletion_requested: None,
+            persist_config: options.config.is_none(),
+        };
+
+        if let Some(profile) = &options.profile {
+            let result = if application.config_profiles.contains(profile) {
+                application.config_profiles.switch_to(profile)
+            } else {
+                application
+                    .config_profiles
+                    .create(profile, Config::default())
+            };
+            if let Err(err) = result {
+                log::error!("{err}");
+            }
+        }
+        let config = options
+            .config
+            .unwrap_or_else(|| application.config_profiles.active().clone());
+        application.apply_config(&config);
+        application.config_history.reset(application.config());
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(path) = &options.record_video {
+            application.start_video_recording(path);
+        }
+
+        if let Some(binding) = application.input.key_bindings().binding(Action::ShowHelp) {
+            log::info!("Press {binding} for help.");
+        }
+
+        Ok(application)
     }
 
-    fn create_triangle_render_pipeline(
-        pipeline_manager: &mut PipelineManager,
-        device: &wgpu::Device,
-    ) -> RenderPipelineHandle {
-        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-            label: None,
-            bind_group_layouts: &[],
-            push_constant_ranges: &[],
-        });
+    /// Applies all settings of a config.
+    fn apply_config(&mut self, config: &Config) {
+        self.screen
+            .set_present_mode(&self.device, config.present_mode.into());
+        self.screen
+            .set_desired_maximum_frame_latency(&self.device, config.desired_maximum_frame_latency);
+        self.time.set_time_scale(config.time_scale);
+        self.set_dynamic_resolution(config.dynamic_resolution);
+        let target_frame_time_ms = config.target_frame_time_ms.clamp(
+            *DynamicResolution::TARGET_FRAME_TIME_MS_RANGE.start(),
+            *DynamicResolution::TARGET_FRAME_TIME_MS_RANGE.end(),
+        );
+        // Clamping passes NaN through, which fails the conversion.
+        self.dynamic_resolution.set_target_frame_time(
+            web_time::Duration::try_from_secs_f32(target_frame_time_ms / 1000.0)
+                .unwrap_or(DynamicResolution::DEFAULT_TARGET_FRAME_TIME),
+        );
+        self.hdr_backbuffer.set_dithering(config.dithering);
+        self.screenshot_scale = config.screenshot_scale.clamp(
+            *screenshot::SCALE_RANGE.start(),
+            *screenshot::SCALE_RANGE.end(),
+        );
+    }
 
-        pipeline_manager
-            .create_render_pipeline(
-                device,
-                RenderPipelineDescriptor {
-                    debug_label: "triangle".to_owned(),
-                    layout: pipeline_layout,
-                    vertex_shader: ShaderEntryPoint::first_in("shader.wgsl"),
-                    fragment_shader: ShaderEntryPoint::first_in("shader.wgsl"),
-                    fragment_targets: vec![HdrBackbuffer::FORMAT.into()],
-                    primitive: wgpu::PrimitiveState::default(),
-                    depth_stencil: None,
-                    multisample: wgpu::MultisampleState::default(),
-                },
-            )
-            .unwrap()
+    /// Gathers all settings that are persisted in the config.
+    fn config(&self) -> Config {
+        let mut config = Config::default();
+        config.present_mode = self.screen.present_mode().into();
+        config.desired_maximum_frame_latency = self.screen.desired_maximum_frame_latency();
+        config.time_scale = self.time.time_scale();
+        config.screenshot_scale = self.screenshot_scale;
+        config.dithering = self.hdr_backbuffer.dithering();
+        config.dynamic_resolution = self.dynamic_resolution.is_enabled();
+        config.target_frame_time_ms =
+            self.dynamic_resolution.target_frame_time().as_secs_f32() * 1000.0;
+        config
+    }
+
+    /// Saves the current settings to the active config profile.
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))] // Configs aren't persisted on the web.
+    pub fn save_config(&mut self) {
+        if !self.persist_config {
+            return;
+        }
+        self.config_profiles.set_active(self.config());
+        if let Err(err) = self.config_profiles.save_active() {
+            log::error!("{err}");
+        }
+    }
+
+    /// Undo & redo of settings changes, changes from this frame are recorded afterwards.
+    fn handle_undo_redo(&mut self) {
+        if self.input.is_action_pressed(Action::Undo) {
+            if let Some(config) = self.config_history.undo().cloned() {
+                self.apply_config(&config);
+            }
+        } else if self.input.is_action_pressed(Action::Redo) {
+            if let Some(config) = self.config_history.redo().cloned() {
+                self.apply_config(&config);
+            }
+        } else if self.input.is_action_pressed(Action::LogConfigHistory) {
+            self.config_history.log_history();
+        }
+
+        self.config_history.record(&self.config());
+    }
+
+    /// Applies edits of the active config profile's file made by other programs.
+    ///
+    /// Reloaded settings are recorded in the config history like any other change, so they can be undone.
+    /// Disabled when starting with a config file, since the active profile isn't what's running then.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn reload_external_config_edits(&mut self) {
+        if !self.persist_config {
+            return;
+        }
+        if let Some(config) = self.config_profiles.reload_external_edits(&self.config()) {
+            log::info!(
+                "Reloaded external edits of config profile {:?}",
+                self.config_profiles.active_name()
+            );
+            self.apply_config(&config);
+        }
+    }
+
+    /// Switching, creating, duplicating & deleting of config profiles.
+    ///
+    /// Disabled when starting with a config file, since any of these would save that config to the active profile.
+    fn handle_config_profile_controls(&mut self) {
+        let pressed = |action| self.input.is_action_pressed(action);
+        if !self.persist_config {
+            let profile_actions = [
+                Action::NextConfigProfile,
+                Action::NewConfigProfile,
+                Action::DuplicateConfigProfile,
+                Action::DeleteConfigProfile,
+            ];
+            if profile_actions.into_iter().any(pressed) {
+                log::warn!("Config profiles can't be changed when starting with a config file.");
+            }
+            return;
+        }
+        let current_config = self.config();
+        let profiles = &mut self.config_profiles;
+
+        let result = if pressed(Action::NextConfigProfile) {
+            let next = profiles.next_name().to_owned();
+            profiles.set_active(current_config);
+            profiles.switch_to(&next)
+        } else if pressed(Action::NewConfigProfile) {
+            profiles.set_active(current_config);
+            profiles.create_with_unique_name("profile", Config::default())
+        } else if pressed(Action::DuplicateConfigProfile) {
+            let name = format!("{}_copy", profiles.active_name());
+            profiles.set_active(current_config.clone());
+            profiles.create_with_unique_name(&name, current_config)
+        } else if pressed(Action::DeleteConfigProfile) {
+            // Deletion removes the profile's file, so a single accidental press must not be enough.
+            let confirmed = self
+                .profile_deletion_requested
+                .take()
+                .is_some_and(|requested| {
+                    requested.elapsed() < PROFILE_DELETION_CONFIRMATION_TIMEOUT
+                });
+            if !confirmed {
+                self.profile_deletion_requested = Some(web_time::Instant::now());
+                if let Some(binding) = self
+                    .input
+                    .key_bindings()
+                    .binding(Action::DeleteConfigProfile)
+                {
+                    log::warn!(
+                        "Press {binding} again within {} seconds to delete config profile {:?}.",
+                        PROFILE_DELETION_CONFIRMATION_TIMEOUT.as_secs(),
+                        profiles.active_name()
+                    );
+                }
+                return;
+            }
+            profiles.delete_active()
+        } else {
+            return;
+        };
+
+        match result {
+            Ok(config) => {
+                let config = config.clone();
+                log::info!(
+                    "Active config profile: {:?}",
+                    self.config_profiles.active_name()
+                );
+                self.apply_config(&config);
+                // Undoing would otherwise apply settings of the previous profile to this one.
+                self.config_history.reset(self.config());
+            }
+            Err(err) => log::error!("{err}"),
+        }
     }
 
     pub fn update(&mut self) {
         self.active_frame_index += 1;
+        self.input
+            .begin_frame(&self.window, self.time.time_since_last_frame());
+        let real_delta = self.input.real_delta();
+        #[cfg(not(target_arch = "wasm32"))]
+        let real_delta = self.video_recorder.frame_duration().unwrap_or(real_delta);
+        self.time.advance_frame(real_delta);
         self.pipeline_manager.reload_changed_pipelines(&self.device);
+        self.texture_manager
+            .reload_changed_textures(&self.device, &self.queue);
+        self.readback_belt.poll(&self.device);
+        self.readback_belt.begin_frame(self.active_frame_index);
+        self.submission_timer.poll(&self.device);
+        self.gpu_timer.report();
+        self.pipeline_statistics.report();
+        self.update_dynamic_resolution();
 
-        let current_resolution =
-            glam::uvec2(self.window.get_size().0 as _, self.window.get_size().1 as _);
+        if self.input.is_action_pressed(Action::CyclePresentMode) {
+            self.cycle_present_mode();
+        }
+        if self.input.is_action_pressed(Action::CycleFrameLatency) {
+            self.cycle_frame_latency();
+        }
+        self.handle_time_controls();
+        self.handle_config_profile_controls();
+        #[cfg(not(target_arch = "wasm32"))]
+        self.reload_external_config_edits();
+        if self.input.is_action_pressed(Action::ShowHelp) {
+            self.log_help();
+        }
+        if self.input.is_action_pressed(Action::LogErrorHistory) {
+            self.error_tracker.log_error_history();
+        }
+        if self.input.is_action_pressed(Action::DumpFrame) {
+            self.debug_dump_requested = true;
+        }
+        if self.input.is_action_pressed(Action::LogCompileTimes) {
+            self.pipeline_manager.log_compile_times();
+        }
+        if self.input.is_action_pressed(Action::CycleWireframeMode) {
+            self.cycle_wireframe_mode();
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.input.is_action_pressed(Action::ToggleVideoRecording) {
+            self.toggle_video_recording();
+        }
+        if self.input.is_action_pressed(Action::CycleScreenshotScale) {
+            self.cycle_screenshot_scale();
+        }
+        if self.input.is_action_pressed(Action::TakeScreenshot) {
+            self.screenshot_requested = true;
+        }
+        if self
+            .input
+            .is_action_pressed(Action::ToggleSubmissionTimings)
+        {
+            let log_reports = !self.submission_timer.logs_reports();
+            self.submission_timer.set_log_reports(log_reports);
+            self.gpu_timer.set_log_reports(log_reports);
+            self.pipeline_statistics.set_enabled(log_reports);
+            log::info!("GPU & CPU timings: {log_reports}");
+            if log_reports && !self.gpu_timer.is_supported() {
+                log::info!("Timestamp queries are not supported, falling back to coarse submission timings.");
+            }
+            if log_reports && !self.pipeline_statistics.is_supported() {
+                log::info!("Pipeline statistics queries are not supported, not logging them.");
+            }
+            if log_reports && self.submission_timer.is_active() && !self.split_submissions {
+                // Submission timings are per submission, passes can only be told apart if they're submitted separately.
+                self.split_submissions = true;
+                log::info!("Split submissions: true");
+            }
+        }
+        if self.input.is_action_pressed(Action::ToggleSplitSubmissions) {
+            self.split_submissions = !self.split_submissions;
+            log::info!("Split submissions: {}", self.split_submissions);
+        }
+        if self
+            .input
+            .is_action_pressed(Action::ToggleDynamicResolution)
+        {
+            self.set_dynamic_resolution(!self.dynamic_resolution.is_enabled());
+            log::info!(
+                "Dynamic resolution: {}",
+                self.dynamic_resolution.is_enabled()
+            );
+        }
+        if self.input.is_action_pressed(Action::ToggleDithering) {
+            self.hdr_backbuffer
+                .set_dithering(!self.hdr_backbuffer.dithering());
+            log::info!("Dithering: {}", self.hdr_backbuffer.dithering());
+        }
+        if self.input.is_action_pressed(Action::CaptureFrame) {
+            self.frame_capture.request_capture();
+        }
+        self.handle_undo_redo();
+        self.update_error_badge();
 
-        if self.screen.resolution() != current_resolution
-            // Ignore zero sized windows, lots of resize operations can't handle this.
-            && current_resolution.x != 0
-            && current_resolution.y != 0
+        let window_resolution = glam::uvec2(
+            self.input.window_size().0 as _,
+            self.input.window_size().1 as _,
+        );
+        if self.screen.on_resize(&self.device, window_resolution) {
+            self.hdr_backbuffer
+                .on_resize(&self.device, self.screen.resolution());
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if self
+            .video_recorder
+            .resolution()
+            .is_some_and(|resolution| resolution != self.screen.output_resolution())
         {
-            self.screen.on_resize(&self.device, current_resolution);
+            // Videos have a fixed resolution.
+            log::warn!("Resolution changed, stopping video recording.");
+            self.stop_video_recording();
+        }
+    }
+
+    /// Switches to the next present mode supported by the surface.
+    fn cycle_present_mode(&mut self) {
+        let present_modes = std::iter::once(Screen::DEFAULT_PRESENT_MODE)
+            .chain(self.screen.supported_present_modes().iter().copied())
+            .collect::<Vec<_>>();
+        let current_index = present_modes
+            .iter()
+            .position(|mode| *mode == self.screen.present_mode())
+            .unwrap_or(0);
+        let next_present_mode = present_modes[(current_index + 1) % present_modes.len()];
+
+        self.screen
+            .set_present_mode(&self.device, next_present_mode);
+        log::info!("Present mode: {:?}", self.screen.present_mode());
+    }
+
+    /// Switches to the next desired maximum frame latency, wrapping around at the end of the range.
+    fn cycle_frame_latency(&mut self) {
+        let latency = self.screen.desired_maximum_frame_latency() + 1;
+        let latency = if Screen::FRAME_LATENCY_RANGE.contains(&latency) {
+            latency
+        } else {
+            *Screen::FRAME_LATENCY_RANGE.start()
+        };
+
+        self.screen
+            .set_desired_maximum_frame_latency(&self.device, latency);
+        log::info!(
+            "Desired maximum frame latency: {}",
+            self.screen.desired_maximum_frame_latency()
+        );
+    }
+
+    fn cycle_wireframe_mode(&mut self) {
+        if self.triangle_wireframe_pipeline.is_none() {
+            log::warn!("Wireframe rendering is not supported by the device.");
+            return;
+        }
+        self.wireframe_mode = self.wireframe_mode.next();
+        log::info!("Wireframe mode: {:?}", self.wireframe_mode);
+    }
+
+    /// Doubles the screenshot scale, wrapping around at the end of the range.
+    fn cycle_screenshot_scale(&mut self) {
+        let scale = self.screenshot_scale * 2;
+        self.screenshot_scale = if screenshot::SCALE_RANGE.contains(&scale) {
+            scale
+        } else {
+            *screenshot::SCALE_RANGE.start()
+        };
+        log::info!("Screenshot scale: {}x", self.screenshot_scale);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn toggle_video_recording(&mut self) {
+        if self.video_recorder.is_recording() {
+            self.stop_video_recording();
+        } else {
+            match video_recorder::VideoRecorder::default_path() {
+                Ok(path) => self.start_video_recording(&path),
+                Err(err) => log::error!("Failed to start video recording: {err}"),
+            }
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn start_video_recording(&mut self, path: &std::path::Path) {
+        if let Err(err) = self.video_recorder.start(
+            &self.device,
+            path,
+            self.screen.output_resolution(),
+            self.screen.surface_format(),
+        ) {
+            log::error!("Failed to start video recording: {err}");
+        }
+    }
+
+    /// Stops video recording after waiting for all outstanding frames.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn stop_video_recording(&mut self) {
+        if !self.video_recorder.is_recording() {
+            return;
+        }
+        self.device.poll(wgpu::Maintain::Wait);
+        self.readback_belt.poll(&self.device);
+        self.video_recorder.stop();
+    }
+
+    fn set_dynamic_resolution(&mut self, enabled: bool) {
+        if enabled == self.dynamic_resolution.is_enabled() {
+            return;
+        }
+        // On native, fence timings only tell when the device was polled, see `SubmissionTimer`.
+        if enabled && !self.gpu_timer.is_supported() && cfg!(not(target_arch = "wasm32")) {
+            log::warn!(
+                "Dynamic resolution requires timestamp queries, which adapter {:?} doesn't support.",
+                self.adapter.get_info().name
+            );
+            return;
+        }
+        self.dynamic_resolution.set_enabled(enabled);
+        // GPU frame times are measured with timestamp queries if possible, otherwise (on the web) by the submission timer.
+        if self.gpu_timer.is_supported() {
+            self.gpu_timer.set_record_frame_times(enabled);
+        } else {
+            self.submission_timer.set_record_frame_times(enabled);
+        }
+        if !enabled {
+            self.set_render_scale(1.0);
+        }
+    }
+
+    /// Adapts the render scale to the GPU frame times of recently completed frames.
+    fn update_dynamic_resolution(&mut self) {
+        let frame_times = if self.gpu_timer.is_supported() {
+            self.gpu_timer.take_frame_times()
+        } else {
+            self.submission_timer.take_frame_times()
+        };
+        for frame_time in frame_times {
+            if let Some(render_scale) = self
+                .dynamic_resolution
+                .record_frame_time(frame_time, self.screen.render_scale())
+            {
+                self.set_render_scale(render_scale);
+                log::info!(
+                    "Render scale: {:.2} ({})",
+                    self.screen.render_scale(),
+                    self.screen.resolution()
+                );
+            }
+        }
+    }
+
+    fn set_render_scale(&mut self, render_scale: f32) {
+        if self.screen.set_render_scale(render_scale) {
             self.hdr_backbuffer
-                .on_resize(&self.device, current_resolution);
+                .on_resize(&self.device, self.screen.resolution());
+        }
+    }
+
+    /// Logs key bindings, adapter & build info.
+    fn log_help(&self) {
+        let key_bindings = self
+            .input
+            .key_bindings()
+            .iter()
+            .map(|(action, binding)| {
+                format!("  {:<8} {}", binding.to_string(), action.description())
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let adapter_info = self.adapter.get_info();
+
+        log::info!(
+            "{WINDOW_TITLE} {} ({}, built {})\n\
+            https://github.com/Wumpf/terrain_and_stuff\n\n\
+            Controls:\n{key_bindings}\n\n\
+            Adapter: {} ({:?}, {:?} backend)\n\
+            Driver: {} {}\n\
+            GPU capabilities:\n{}",
+            env!("CARGO_PKG_VERSION"),
+            env!("GIT_HASH"),
+            env!("BUILD_DATE"),
+            adapter_info.name,
+            adapter_info.device_type,
+            adapter_info.backend,
+            adapter_info.driver,
+            adapter_info.driver_info,
+            self.capabilities,
+        );
+    }
+
+    /// Indicates in the window title whether any wgpu errors occurred.
+    fn update_error_badge(&mut self) {
+        let error_count = self.error_tracker.total_error_count();
+        if error_count == self.displayed_error_count {
+            return;
+        }
+        self.displayed_error_count = error_count;
+        let list_hint = self
+            .input
+            .key_bindings()
+            .binding(Action::LogErrorHistory)
+            .map(|binding| format!(" ({binding} to list)"))
+            .unwrap_or_default();
+        self.window.set_title(&format!(
+            "{WINDOW_TITLE} - ⚠ {error_count} wgpu errors{list_hint}"
+        ));
+    }
+
+    /// Pause, slow motion & scrubbing of the simulation clock.
+    fn handle_time_controls(&mut self) {
+        if self.input.is_action_pressed(Action::TogglePause) {
+            self.time.set_paused(!self.time.is_paused());
+            log::info!("Simulation paused: {}", self.time.is_paused());
+        }
+        if self.input.is_action_pressed(Action::SlowDown) {
+            self.time.set_time_scale(self.time.time_scale() * 0.5);
+            log::info!("Simulation time scale: {}", self.time.time_scale());
+        }
+        if self.input.is_action_pressed(Action::SpeedUp) {
+            self.time.set_time_scale(self.time.time_scale() * 2.0);
+            log::info!("Simulation time scale: {}", self.time.time_scale());
+        }
+
+        const SCRUB_SPEED_SECONDS_PER_SECOND: f32 = 10.0;
+        let scrub_amount = self.time.real_delta().as_secs_f32() * SCRUB_SPEED_SECONDS_PER_SECOND;
+        if self.input.is_action_down(Action::ScrubBackward) {
+            self.time.scrub(-scrub_amount);
+        }
+        if self.input.is_action_down(Action::ScrubForward) {
+            self.time.scrub(scrub_amount);
         }
     }
 
     pub fn draw(&mut self) {
         let error_scope = WgpuErrorScope::start(&self.device);
 
-        let Some(frame) = self.screen.start_frame(&self.device) else {
+        let Some(frame) = self.submission_timer.time_cpu("get_current_texture", || {
+            self.screen.start_frame(&self.device)
+        }) else {
             return;
         };
         let view = frame
             .texture
             .create_view(&wgpu::TextureViewDescriptor::default());
 
+        self.frame_capture
+            .begin_frame(&self.device, self.active_frame_index);
+
+        if std::mem::take(&mut self.screenshot_requested) {
+            self.draw_screenshot();
+        }
+
+        self.update_frame_uniform_buffer(self.screen.resolution());
+        self.submission_timer.begin_frame(&self.queue);
+
+        let split_submissions = self.split_submissions;
+
         let mut encoder = self
             .device
             .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                label: Some("Main encoder"),
+                label: Some("Frame encoder"),
             });
+        encoder.insert_debug_marker(&format!("Frame {}", self.active_frame_index));
+        self.gpu_timer.begin_scope(&mut encoder, "Scene");
+        self.draw_scene(&mut DebugGroup::new(&mut encoder, "Scene"), "Scene");
+        if std::mem::take(&mut self.debug_dump_requested) {
+            self.draw_debug_dump(&mut DebugGroup::new(&mut encoder, "Debug dump"));
+        }
+        self.gpu_timer.end_scope(&mut encoder);
+        if split_submissions {
+            self.submission_timer
+                .submit(&self.queue, "Scene", encoder.finish());
+            encoder = self
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("Output encoder"),
+                });
+        }
 
-        self.draw_scene(&mut encoder);
-        self.hdr_backbuffer
-            .display_transform(&view, &mut encoder, &self.pipeline_manager);
+        self.gpu_timer.begin_scope(&mut encoder, "Output");
+        self.hdr_backbuffer.display_transform(
+            &view,
+            self.screen.letterbox(),
+            &mut DebugGroup::new(&mut encoder, "Display transform"),
+            &self.pipeline_manager,
+        );
+        #[cfg(not(target_arch = "wasm32"))]
+        self.video_recorder.record_frame(
+            &self.device,
+            &mut encoder,
+            &mut self.readback_belt,
+            &self.hdr_backbuffer,
+            &self.pipeline_manager,
+        );
+        self.gpu_timer.end_scope(&mut encoder);
+        self.pipeline_statistics
+            .end_frame(&self.device, &mut encoder, &mut self.readback_belt);
+        self.gpu_timer
+            .end_frame(&self.device, &mut encoder, &mut self.readback_belt);
 
-        let command_buffer = encoder.finish();
-        self.queue.submit(Some(command_buffer));
-        frame.present();
+        self.submission_timer.submit(
+            &self.queue,
+            if split_submissions { "Output" } else { "Frame" },
+            encoder.finish(),
+        );
+        self.readback_belt.after_queue_submit();
+        self.submission_timer
+            .time_cpu("present", || frame.present());
+
+        self.frame_capture.end_frame(&self.device);
 
         {
             let frame_index_for_uncaptured_errors = self.frame_index_for_uncaptured_errors.clone();
@@ -244,7 +928,126 @@ impl<'a> Application<'a> {
         }
     }
 
-    fn draw_scene(&mut self, encoder: &mut wgpu::CommandEncoder) {
+    /// Renders the scene offscreen at the screenshot scale and schedules saving it once read back.
+    fn draw_screenshot(&mut self) {
+        let screen_resolution = self.screen.resolution();
+        let format = self.screen.surface_format();
+        let resolution = screenshot::screenshot_resolution(
+            &self.device.limits(),
+            self.screen.output_resolution(),
+            format,
+            self.screenshot_scale,
+        );
+        let target = screenshot::create_target(&self.device, "Screenshot", resolution, format);
+        let view = target.create_view(&wgpu::TextureViewDescriptor::default());
+
+        // Render at screenshot resolution, everything that depends on the resolution needs to be adjusted temporarily.
+        self.hdr_backbuffer.on_resize(&self.device, resolution);
+        self.update_frame_uniform_buffer(resolution);
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Screenshot encoder"),
+            });
+        {
+            let mut encoder = DebugGroup::new(&mut encoder, "Screenshot");
+            // Its own pipeline statistics label, so screenshot resolution counts don't mix with regular frames.
+            self.draw_scene(
+                &mut DebugGroup::new(&mut encoder, "Scene"),
+                "Screenshot scene",
+            );
+            self.hdr_backbuffer.display_transform(
+                &view,
+                None,
+                &mut DebugGroup::new(&mut encoder, "Display transform"),
+                &self.pipeline_manager,
+            );
+        }
+        self.readback_belt.read_texture(
+            &self.device,
+            &mut encoder,
+            target.as_image_copy(),
+            format,
+            target.size(),
+            move |readback| screenshot::save(resolution, format, readback.data.to_vec()),
+        );
+
+        self.queue.submit(Some(encoder.finish()));
+        self.readback_belt.after_queue_submit();
+
+        self.hdr_backbuffer
+            .on_resize(&self.device, screen_resolution);
+    }
+
+    /// Schedules readbacks of all intermediate targets of the current frame & saves them to disk once they arrive.
+    fn draw_debug_dump(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        let dump = DebugDump::new(self.active_frame_index);
+        log::info!(
+            "Dumping frame {} to {:?}",
+            self.active_frame_index,
+            dump.directory()
+        );
+
+        let hdr_backbuffer = self.hdr_backbuffer.texture();
+        let resolution = glam::uvec2(hdr_backbuffer.width(), hdr_backbuffer.height());
+        {
+            let dump = dump.clone();
+            self.readback_belt.read_texture(
+                &self.device,
+                encoder,
+                hdr_backbuffer.as_image_copy(),
+                HdrBackbuffer::FORMAT,
+                hdr_backbuffer.size(),
+                move |readback| {
+                    dump.save(
+                        "hdr_backbuffer",
+                        resolution,
+                        HdrBackbuffer::FORMAT,
+                        readback.data.to_vec(),
+                    )
+                },
+            );
+        }
+
+        // The surface can't be read back, so apply the display transform once more to a target that can.
+        let format = self.screen.surface_format();
+        let target =
+            screenshot::create_target(&self.device, "Debug dump output", resolution, format);
+        self.hdr_backbuffer.display_transform(
+            &target.create_view(&wgpu::TextureViewDescriptor::default()),
+            None,
+            &mut DebugGroup::new(encoder, "Display transform"),
+            &self.pipeline_manager,
+        );
+        self.readback_belt.read_texture(
+            &self.device,
+            encoder,
+            target.as_image_copy(),
+            format,
+            target.size(),
+            move |readback| dump.save("output", resolution, format, readback.data.to_vec()),
+        );
+    }
+
+    fn update_frame_uniform_buffer(&self, resolution: glam::UVec2) {
+        let resolution = resolution.as_vec2();
+        self.global_bindings.update_frame_uniform_buffer(
+            &self.queue,
+            &FrameUniformBuffer {
+                resolution: resolution.into(),
+                inv_resolution: resolution.recip().into(),
+                jitter_offset: [0.0; 2],
+                frame_index: self.active_frame_index as u32,
+                elapsed_time: self.time.simulation_time().as_secs_f32(),
+                delta_time: self.time.simulation_delta().as_secs_f32(),
+                real_delta_time: self.time.real_delta().as_secs_f32(),
+            },
+        );
+    }
+
+    /// Draws the scene into the HDR backbuffer, measured under `statistics_label` by the pipeline statistics.
+    fn draw_scene(&mut self, encoder: &mut wgpu::CommandEncoder, statistics_label: &'static str) {
         let mut hdr_rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: None,
             color_attachments: &[Some(wgpu::RenderPassColorAttachment {
@@ -260,15 +1063,31 @@ impl<'a> Application<'a> {
             occlusion_query_set: None,
         });
 
+        let hdr_backbuffer = self.hdr_backbuffer.texture();
+        self.pipeline_statistics.begin_pass(
+            &mut hdr_rpass,
+            statistics_label,
+            hdr_backbuffer.width() as u64 * hdr_backbuffer.height() as u64,
+        );
+
+        hdr_rpass.set_bind_group(0, Some(self.global_bindings.bind_group()), &[]);
         self.sky.draw(&mut hdr_rpass, &self.pipeline_manager);
 
-        if let Some(pipeline) = self
-            .pipeline_manager
-            .get_render_pipeline(self.triangle_render_pipeline)
-        {
-            hdr_rpass.set_pipeline(pipeline);
-            hdr_rpass.draw(0..3, 0..1);
+        let mut triangle_pipelines = Vec::new();
+        if self.wireframe_mode.draws_shaded() {
+            triangle_pipelines.push(self.triangle_render_pipeline);
         }
+        if self.wireframe_mode.draws_wireframe() {
+            triangle_pipelines.extend(self.triangle_wireframe_pipeline);
+        }
+        for pipeline in triangle_pipelines {
+            if let Some(pipeline) = self.pipeline_manager.get_render_pipeline(pipeline) {
+                hdr_rpass.set_pipeline(pipeline);
+                hdr_rpass.draw(0..3, 0..1);
+            }
+        }
+
+        self.pipeline_statistics.end_pass(&mut hdr_rpass);
     }
 }
 
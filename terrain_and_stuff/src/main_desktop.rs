@@ -1,29 +1,70 @@
-use crate::Application;
+use clap::Parser as _;
+
+use crate::{cli::CliArgs, key_bindings::Action, Application};
 
 pub fn main_desktop() -> anyhow::Result<()> {
     env_logger::init_from_env(env_logger::Env::default().filter_or(
         env_logger::DEFAULT_FILTER_ENV,
-        "warn,terrain_and_stuff=info",
+        "warn,terrain_and_stuff=info,terrain_renderer=info",
     ));
 
-    let mut application = pollster::block_on(Application::new())?;
+    let cli_args = CliArgs::parse();
+    if cli_args.validate_shaders {
+        return Ok(terrain_renderer::resource_managers::validate_all_shaders()?);
+    }
+
+    let mut benchmark = cli_args.benchmark();
+    let options = cli_args.into_startup_options()?;
+    let mut application = pollster::block_on(Application::new(options))?;
+
+    if benchmark.is_some() {
+        // Without timestamp queries, the report would silently lack all GPU timings.
+        anyhow::ensure!(
+            application.gpu_timer.is_supported(),
+            "Benchmarks need timestamp queries for GPU timings, which adapter {:?} doesn't support",
+            application.adapter.get_info().name
+        );
+        application.gpu_timer.set_record_scope_times(true);
+    }
 
     loop {
+        let frame_start = web_time::Instant::now();
+
         application.window.update();
+        // Checked on the window directly, so exiting works during input replay as well.
         if application
-            .window
-            .is_key_pressed(minifb::Key::Escape, minifb::KeyRepeat::No)
+            .input
+            .key_bindings()
+            .binding(Action::Exit)
+            .is_some_and(|binding| binding.is_pressed_in_window(&application.window))
         {
-            return Ok(());
+            break;
         }
 
         // It's important to check openness after updating the window.
         // Otherwise, wgpu's surface might be invalid now.
         if !application.window.is_open() {
-            return Ok(());
+            break;
         }
 
         application.update();
         application.draw();
+
+        if application.input.is_replay_finished() {
+            break;
+        }
+
+        if let Some(benchmark) = &mut benchmark {
+            let gpu_scope_times = application.gpu_timer.take_scope_times();
+            if benchmark.record_frame(frame_start.elapsed(), gpu_scope_times) {
+                benchmark.write_report()?;
+                break;
+            }
+        }
     }
+
+    application.stop_video_recording();
+    application.save_config();
+
+    Ok(())
 }
@@ -2,7 +2,7 @@ use std::{cell::RefCell, panic, rc::Rc};
 use wasm_bindgen::{prelude::*, JsCast};
 use wgpu::web_sys;
 
-use crate::Application;
+use crate::{Application, StartupOptions};
 
 fn window() -> web_sys::Window {
     web_sys::window().expect("no global `window` exists")
@@ -19,7 +19,7 @@ pub async fn start() {
     panic::set_hook(Box::new(console_error_panic_hook::hook));
     console_log::init().expect("could not initialize logger");
 
-    let mut application = Application::new().await.unwrap();
+    let mut application = Application::new(StartupOptions::default()).await.unwrap();
 
     // A reference counted pointer to the closure that will update and render the application.
     let update_closure = Rc::new(RefCell::new(None));
@@ -0,0 +1,191 @@
+//! Debug dumps: intermediate render targets of a single frame saved to disk.
+//!
+//! Meant for inspecting shading issues offline, e.g. ones reported by someone else.
+//! Float targets are saved as PFM (portable float map) to keep the full HDR range,
+//! display referred targets as PNG.
+
+use std::path::PathBuf;
+
+/// Directory debug dumps are saved to, relative to the working directory.
+const DEBUG_DUMP_DIRECTORY: &str = "debug_dumps";
+
+/// All targets of a dumped frame, each saved as soon as its readback arrives.
+#[derive(Clone)]
+pub struct DebugDump {
+    directory: PathBuf,
+}
+
+impl DebugDump {
+    /// Starts a dump into a new timestamped directory.
+    pub fn new(frame_index: u64) -> Self {
+        let timestamp = web_time::SystemTime::now()
+            .duration_since(web_time::UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_millis());
+
+        Self {
+            directory: PathBuf::from(DEBUG_DUMP_DIRECTORY)
+                .join(format!("dump_{timestamp}_frame_{frame_index}")),
+        }
+    }
+
+    /// Saves tightly packed target data on a background thread.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save(
+        &self,
+        name: &'static str,
+        resolution: glam::UVec2,
+        format: wgpu::TextureFormat,
+        data: Vec<u8>,
+    ) {
+        let directory = self.directory.clone();
+        std::thread::spawn(move || {
+            let result = std::fs::create_dir_all(&directory)
+                .map_err(anyhow::Error::from)
+                .and_then(|()| match format {
+                    wgpu::TextureFormat::Rgba16Float => {
+                        let path = directory.join(format!("{name}.pfm"));
+                        write_pfm(&path, resolution, &data).map(|()| path)
+                    }
+                    _ => {
+                        let path = directory.join(format!("{name}.png"));
+                        super::screenshot::write_png(&path, resolution, format, data).map(|()| path)
+                    }
+                });
+            match result {
+                Ok(path) => log::info!("Saved {path:?}"),
+                Err(err) => log::error!("Failed to save {name:?} of debug dump: {err:#}"),
+            }
+        });
+    }
+
+    /// Not supported on the web yet, only logs a warning.
+    #[cfg(target_arch = "wasm32")]
+    pub fn save(
+        &self,
+        name: &'static str,
+        _resolution: glam::UVec2,
+        _format: wgpu::TextureFormat,
+        _data: Vec<u8>,
+    ) {
+        log::warn!("Saving {name:?} of debug dump is not supported on the web yet.");
+    }
+
+    /// Directory all images of this dump are written to.
+    pub fn directory(&self) -> &std::path::Path {
+        &self.directory
+    }
+}
+
+/// Writes tightly packed `Rgba16Float` data as little endian RGB PFM, dropping alpha.
+#[cfg(not(target_arch = "wasm32"))]
+fn write_pfm(path: &std::path::Path, resolution: glam::UVec2, data: &[u8]) -> anyhow::Result<()> {
+    use std::io::Write as _;
+
+    let bytes_per_row = resolution.x as usize * 8;
+    anyhow::ensure!(
+        data.len() == bytes_per_row * resolution.y as usize,
+        "Expected {}x{} pixels of Rgba16Float data, got {} bytes",
+        resolution.x,
+        resolution.y,
+        data.len()
+    );
+
+    let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    // Negative scale means little endian.
+    write!(file, "PF\n{} {}\n-1.0\n", resolution.x, resolution.y)?;
+
+    // PFM rows go from bottom to top.
+    for row in data.chunks_exact(bytes_per_row).rev() {
+        for pixel in row.chunks_exact(8) {
+            for channel in pixel[..6].chunks_exact(2) {
+                let value = f32_from_f16(u16::from_le_bytes([channel[0], channel[1]]));
+                file.write_all(&value.to_le_bytes())?;
+            }
+        }
+    }
+    file.flush()?;
+
+    Ok(())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn f32_from_f16(bits: u16) -> f32 {
+    let sign = if bits & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = ((bits >> 10) & 0x1f) as i32;
+    let mantissa = (bits & 0x3ff) as f32;
+
+    sign * match exponent {
+        0 => mantissa * 2.0_f32.powi(-24),
+        0x1f if mantissa == 0.0 => f32::INFINITY,
+        0x1f => f32::NAN,
+        _ => (1.0 + mantissa / 1024.0) * 2.0_f32.powi(exponent - 15),
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn f32_from_f16_normal() {
+        assert_eq!(f32_from_f16(0x3c00), 1.0);
+        assert_eq!(f32_from_f16(0xc000), -2.0);
+        assert_eq!(f32_from_f16(0x3555), 0.333_251_95);
+        assert_eq!(f32_from_f16(0x7bff), 65504.0);
+        assert_eq!(f32_from_f16(0x0400), 2.0_f32.powi(-14));
+    }
+
+    #[test]
+    fn f32_from_f16_zero_and_subnormal() {
+        assert_eq!(f32_from_f16(0x0000), 0.0);
+        assert!(f32_from_f16(0x8000).is_sign_negative());
+        assert_eq!(f32_from_f16(0x0001), 2.0_f32.powi(-24));
+        assert_eq!(f32_from_f16(0x8001), -(2.0_f32.powi(-24)));
+        assert_eq!(f32_from_f16(0x03ff), 1023.0 * 2.0_f32.powi(-24));
+    }
+
+    #[test]
+    fn f32_from_f16_infinity_and_nan() {
+        assert_eq!(f32_from_f16(0x7c00), f32::INFINITY);
+        assert_eq!(f32_from_f16(0xfc00), f32::NEG_INFINITY);
+        assert!(f32_from_f16(0x7e00).is_nan());
+        assert!(f32_from_f16(0xfc01).is_nan());
+    }
+
+    #[test]
+    fn pfm_rows_go_from_bottom_to_top() {
+        // 2x2 pixels with red values 1 & 2 in the top row, 3 & 4 in the bottom row.
+        let red_values: [u16; 4] = [0x3c00, 0x4000, 0x4200, 0x4400];
+        let data = red_values
+            .iter()
+            .flat_map(|red| [*red, 0, 0, 0x3c00])
+            .flat_map(u16::to_le_bytes)
+            .collect::<Vec<_>>();
+
+        let path = std::env::temp_dir().join(format!(
+            "terrain_and_stuff_pfm_test_{}.pfm",
+            std::process::id()
+        ));
+        write_pfm(&path, glam::uvec2(2, 2), &data).unwrap();
+        let file = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let header = b"PF\n2 2\n-1.0\n";
+        assert_eq!(&file[..header.len()], header);
+        let values = file[header.len()..]
+            .chunks_exact(4)
+            .map(|bytes| f32::from_le_bytes(bytes.try_into().unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            values,
+            [3.0, 0.0, 0.0, 4.0, 0.0, 0.0, 1.0, 0.0, 0.0, 2.0, 0.0, 0.0]
+        );
+    }
+
+    #[test]
+    fn pfm_rejects_mismatching_data_size() {
+        let path = std::env::temp_dir().join("terrain_and_stuff_pfm_test_invalid.pfm");
+        assert!(write_pfm(&path, glam::uvec2(2, 2), &[0; 8]).is_err());
+        assert!(!path.exists());
+    }
+}
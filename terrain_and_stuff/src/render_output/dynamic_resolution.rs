@@ -0,0 +1,106 @@
+use web_time::Duration;
+
+/// Adjusts the render scale to keep GPU frame times below a target.
+///
+/// Frame times are averaged over several frames before each decision.
+/// The scale only recovers once there's significant headroom, so it doesn't oscillate between two scales.
+pub struct DynamicResolution {
+    enabled: bool,
+    target_frame_time: Duration,
+
+    accumulated_frame_time: Duration,
+    num_accumulated_frames: u32,
+}
+
+impl Default for DynamicResolution {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DynamicResolution {
+    /// 60 frames per second.
+    pub const DEFAULT_TARGET_FRAME_TIME: Duration = Duration::from_micros(16_666);
+
+    /// Sensible target frame times in milliseconds, configured targets are clamped to this.
+    pub const TARGET_FRAME_TIME_MS_RANGE: std::ops::RangeInclusive<f32> = 1.0..=1000.0;
+
+    /// The render scale never drops below this.
+    pub const MIN_RENDER_SCALE: f32 = 0.5;
+
+    /// Number of frames averaged for each decision.
+    const NUM_FRAMES_PER_DECISION: u32 = 30;
+
+    /// The scale is raised only if the average frame time is below this fraction of the target.
+    const INCREASE_THRESHOLD: f32 = 0.75;
+
+    const DECREASE_FACTOR: f32 = 0.9;
+    const INCREASE_FACTOR: f32 = 1.05;
+
+    /// Starts out disabled.
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            target_frame_time: Self::DEFAULT_TARGET_FRAME_TIME,
+            accumulated_frame_time: Duration::ZERO,
+            num_accumulated_frames: 0,
+        }
+    }
+
+    /// Whether recorded frame times lead to render scale changes.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Enables or disables scaling, discarding all frame times recorded so far.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        self.reset();
+    }
+
+    /// GPU frame time the render scale is adjusted to stay below.
+    pub fn target_frame_time(&self) -> Duration {
+        self.target_frame_time
+    }
+
+    /// Changes the target, discarding all frame times recorded so far.
+    pub fn set_target_frame_time(&mut self, target_frame_time: Duration) {
+        self.target_frame_time = target_frame_time;
+        self.reset();
+    }
+
+    /// Records the GPU time of a frame.
+    ///
+    /// Returns a new render scale if the current one should change.
+    pub fn record_frame_time(&mut self, frame_time: Duration, render_scale: f32) -> Option<f32> {
+        if !self.enabled {
+            return None;
+        }
+
+        self.accumulated_frame_time += frame_time;
+        self.num_accumulated_frames += 1;
+        if self.num_accumulated_frames < Self::NUM_FRAMES_PER_DECISION {
+            return None;
+        }
+
+        let average_frame_time = self.accumulated_frame_time / self.num_accumulated_frames;
+        self.reset();
+
+        let new_render_scale = if average_frame_time > self.target_frame_time {
+            render_scale * Self::DECREASE_FACTOR
+        } else if average_frame_time < self.target_frame_time.mul_f32(Self::INCREASE_THRESHOLD) {
+            render_scale * Self::INCREASE_FACTOR
+        } else {
+            return None;
+        };
+        let new_render_scale = new_render_scale.clamp(Self::MIN_RENDER_SCALE, 1.0);
+
+        (new_render_scale != render_scale).then_some(new_render_scale)
+    }
+
+    /// Discards all recorded frame times, e.g. after the render scale changed.
+    fn reset(&mut self) {
+        self.accumulated_frame_time = Duration::ZERO;
+        self.num_accumulated_frames = 0;
+    }
+}
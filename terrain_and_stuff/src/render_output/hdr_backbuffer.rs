@@ -6,6 +6,8 @@ use crate::{
     wgpu_utils::{BindGroupBuilder, BindGroupLayoutBuilder, BindGroupLayoutWithDesc},
 };
 
+use super::Letterbox;
+
 /// Defines the linear HDR backbuffer and display transform to an LDR surface.
 ///
 /// Assumes HDR Rec.709/sRGB in optical units (no OETF) and applies OETF as part of the display transform.
@@ -17,11 +19,17 @@ pub struct HdrBackbuffer {
     bind_group_layout: BindGroupLayoutWithDesc,
     bind_group: wgpu::BindGroup,
     display_transform_pipeline: RenderPipelineHandle,
+    display_transform_dithered_pipeline: RenderPipelineHandle,
+
+    /// Whether the display transform dithers its output to hide banding.
+    dithering: bool,
 }
 
 impl HdrBackbuffer {
+    /// Format of the backbuffer, all scene pipelines need to render to it.
     pub const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
 
+    /// Creates the backbuffer & the display transform pipelines writing to `output_format`.
     pub fn new(
         device: &wgpu::Device,
         resolution: glam::UVec2,
@@ -35,28 +43,38 @@ impl HdrBackbuffer {
                 multisampled: false,
             })
             .create(device, "Read HDR Backbuffer");
-        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-            label: Some("Display transform"),
-            bind_group_layouts: &[&bind_group_layout.layout],
-            push_constant_ranges: &[],
-        });
+        let pipeline_layout =
+            pipeline_manager.pipeline_layout(device, "Display transform", &[&bind_group_layout]);
 
         let (hdr_backbuffer, hdr_backbuffer_view, bind_group) =
             Self::crate_backbuffer_texture(device, resolution, &bind_group_layout);
 
-        let display_transform_pipeline = pipeline_manager.create_render_pipeline(
+        let display_transform_descriptor = RenderPipelineDescriptor {
+            debug_label: "Display transform".to_owned(),
+            layout: pipeline_layout,
+            vertex_shader: ShaderEntryPoint::first_in("screen_triangle.wgsl"),
+            fragment_shader: ShaderEntryPoint {
+                path: "display_transform.wgsl".into(),
+                function_name: Some("fs_main".to_owned()),
+            },
+            fragment_targets: vec![output_format.into()],
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+        };
+        let display_transform_dithered_pipeline = pipeline_manager.create_render_pipeline(
             device,
             RenderPipelineDescriptor {
-                debug_label: "Display transform".to_owned(),
-                layout: pipeline_layout,
-                vertex_shader: ShaderEntryPoint::first_in("screen_triangle.wgsl"),
-                fragment_shader: ShaderEntryPoint::first_in("display_transform.wgsl"),
-                fragment_targets: vec![output_format.into()],
-                primitive: wgpu::PrimitiveState::default(),
-                depth_stencil: None,
-                multisample: wgpu::MultisampleState::default(),
+                debug_label: "Display transform (dithered)".to_owned(),
+                fragment_shader: ShaderEntryPoint {
+                    path: "display_transform.wgsl".into(),
+                    function_name: Some("fs_main_dithered".to_owned()),
+                },
+                ..display_transform_descriptor.clone()
             },
         )?;
+        let display_transform_pipeline =
+            pipeline_manager.create_render_pipeline(device, display_transform_descriptor)?;
 
         Ok(HdrBackbuffer {
             hdr_backbuffer,
@@ -65,6 +83,8 @@ impl HdrBackbuffer {
             bind_group_layout,
             bind_group,
             display_transform_pipeline,
+            display_transform_dithered_pipeline,
+            dithering: true,
         })
     }
 
@@ -85,7 +105,9 @@ impl HdrBackbuffer {
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
             format: Self::FORMAT,
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC, // For debug dumps.
             view_formats: &[Self::FORMAT],
         });
         let hdr_backbuffer_view = hdr_backbuffer.create_view(&Default::default());
@@ -96,10 +118,17 @@ impl HdrBackbuffer {
         (hdr_backbuffer, hdr_backbuffer_view, bind_group)
     }
 
+    /// The backbuffer itself, e.g. for copying out of it.
+    pub fn texture(&self) -> &wgpu::Texture {
+        &self.hdr_backbuffer
+    }
+
+    /// View for rendering the scene into the backbuffer.
     pub fn texture_view(&self) -> &wgpu::TextureView {
         &self.hdr_backbuffer_view
     }
 
+    /// Recreates the backbuffer at a new resolution, its content is lost.
     pub fn on_resize(&mut self, device: &wgpu::Device, new_resolution: glam::UVec2) {
         let (hdr_backbuffer, hdr_backbuffer_view, bind_group) =
             Self::crate_backbuffer_texture(device, new_resolution, &self.bind_group_layout);
@@ -109,9 +138,21 @@ impl HdrBackbuffer {
         self.bind_group = bind_group;
     }
 
+    /// Whether the display transform dithers its output to hide banding.
+    pub fn dithering(&self) -> bool {
+        self.dithering
+    }
+
+    /// Takes effect with the next display transform.
+    pub fn set_dithering(&mut self, dithering: bool) {
+        self.dithering = dithering;
+    }
+
+    /// Writes the display transformed HDR backbuffer to the target, or a letterboxed area of it.
     pub fn display_transform(
         &self,
         target: &wgpu::TextureView,
+        letterbox: Option<Letterbox>,
         encoder: &mut wgpu::CommandEncoder,
         pipeline_manager: &PipelineManager,
     ) -> Option<()> {
@@ -132,8 +173,22 @@ impl HdrBackbuffer {
             occlusion_query_set: None,
         });
 
-        render_pass
-            .set_pipeline(pipeline_manager.get_render_pipeline(self.display_transform_pipeline)?);
+        let pipeline = if self.dithering {
+            self.display_transform_dithered_pipeline
+        } else {
+            self.display_transform_pipeline
+        };
+        render_pass.set_pipeline(pipeline_manager.get_render_pipeline(pipeline)?);
+        if let Some(Letterbox { offset, size }) = letterbox {
+            render_pass.set_viewport(
+                offset.x as f32,
+                offset.y as f32,
+                size.x as f32,
+                size.y as f32,
+                0.0,
+                1.0,
+            );
+        }
         render_pass.set_bind_group(0, Some(&self.bind_group), &[]);
         render_pass.draw(0..3, 0..1);
 
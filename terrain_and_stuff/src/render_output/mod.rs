@@ -1,8 +1,13 @@
 //! Handling the rendering output pipeline
 //! -> HDR, display transform (tonemapping), screenshot capturing etc.
 
+mod debug_dump;
+mod dynamic_resolution;
 mod hdr_backbuffer;
 mod screen;
+pub mod screenshot;
 
+pub use debug_dump::DebugDump;
+pub use dynamic_resolution::DynamicResolution;
 pub use hdr_backbuffer::HdrBackbuffer;
-pub use screen::Screen;
+pub use screen::{Letterbox, Screen};
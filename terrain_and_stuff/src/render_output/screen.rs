@@ -1,49 +1,221 @@
+/// Area of the surface that is written to if the output doesn't fill the surface.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Letterbox {
+    /// Top left corner of the area in surface pixels.
+    pub offset: glam::UVec2,
+    /// Size of the area in surface pixels.
+    pub size: glam::UVec2,
+}
+
 /// Manages the target surface.
-// TODO: also handle screenshotting in here
+///
+/// Central place for the render resolution: all resolution dependent resources should follow [`Screen::resolution`].
+/// The output resolution is derived from the window size, but clamped to the device's texture size limits
+/// while keeping the window's aspect ratio. The remaining surface area is left black.
+/// The render resolution is the output resolution times the render scale, and upscaled by the display transform.
 pub struct Screen<'a> {
+    /// Last window size the screen was resized to, may exceed device limits.
+    window_resolution: glam::UVec2,
+
+    /// Resolution the surface is configured with, the window size clamped to device limits.
+    surface_resolution: glam::UVec2,
+
+    /// Area of the surface that is written to by the display transform.
+    output_resolution: glam::UVec2,
+
+    /// Fraction of the output resolution that is rendered at.
+    render_scale: f32,
+
+    /// Resolution all rendering happens at.
     resolution: glam::UVec2,
 
+    max_texture_dimension: u32,
+
     surface: wgpu::Surface<'a>,
     surface_format: wgpu::TextureFormat,
+
+    present_mode: wgpu::PresentMode,
+    desired_maximum_frame_latency: u32,
+
+    /// Present modes the surface supports on this adapter.
+    ///
+    /// Never contains the `Auto*` modes, those are resolved by wgpu to one of these.
+    supported_present_modes: Vec<wgpu::PresentMode>,
 }
 
 impl<'a> Screen<'a> {
-    const PRESENT_MODE: wgpu::PresentMode = wgpu::PresentMode::AutoVsync;
+    /// Present mode the surface is configured with initially.
+    pub const DEFAULT_PRESENT_MODE: wgpu::PresentMode = wgpu::PresentMode::AutoVsync;
+    /// Frame latency the surface is configured with initially, same as wgpu's default.
+    pub const DEFAULT_DESIRED_MAXIMUM_FRAME_LATENCY: u32 = 2;
 
+    /// Range of frame latencies that can be requested.
+    ///
+    /// wgpu clamps to whatever the backend supports, so this is merely a sensible range to pick from.
+    pub const FRAME_LATENCY_RANGE: std::ops::RangeInclusive<u32> = 1..=3;
+
+    /// Configures the surface for the given window size with the default present mode & frame latency.
     pub fn new(
         device: &wgpu::Device,
         adapter: &wgpu::Adapter,
         surface: wgpu::Surface<'a>,
-        initial_resolution: glam::UVec2,
+        window_resolution: glam::UVec2,
     ) -> Self {
         let surface_format = pick_surface_format(&surface, adapter);
+        let supported_present_modes = surface.get_capabilities(adapter).present_modes;
+
+        let max_texture_dimension = device.limits().max_texture_dimension_2d;
+        let window_resolution = window_resolution.max(glam::UVec2::ONE);
+        let (surface_resolution, output_resolution) =
+            clamp_resolution(window_resolution, max_texture_dimension);
 
         let mut screen = Screen {
-            resolution: initial_resolution,
+            window_resolution,
+            surface_resolution,
+            output_resolution,
+            render_scale: 1.0,
+            resolution: output_resolution,
+            max_texture_dimension,
 
             surface,
             surface_format,
+
+            present_mode: Self::DEFAULT_PRESENT_MODE,
+            desired_maximum_frame_latency: Self::DEFAULT_DESIRED_MAXIMUM_FRAME_LATENCY,
+            supported_present_modes,
         };
-        screen.configure_surface(device, initial_resolution);
+        screen.configure_surface(device);
         screen
     }
 
-    // pub fn aspect_ratio(&self) -> f32 {
-    //     self.resolution.x as f32 / self.resolution.y as f32
-    // }
-
+    /// Resolution everything is rendered at.
     pub fn resolution(&self) -> glam::UVec2 {
         self.resolution
     }
 
+    /// Area of the surface the display transform should write to, if the output doesn't fill the surface.
+    pub fn letterbox(&self) -> Option<Letterbox> {
+        (self.output_resolution != self.surface_resolution).then(|| Letterbox {
+            offset: (self.surface_resolution - self.output_resolution) / 2,
+            size: self.output_resolution,
+        })
+    }
+
+    /// Resolution of the area the display transform writes to, i.e. the resolution the user sees.
+    pub fn output_resolution(&self) -> glam::UVec2 {
+        self.output_resolution
+    }
+
+    /// Fraction of the output resolution that is rendered at.
+    pub fn render_scale(&self) -> f32 {
+        self.render_scale
+    }
+
+    /// Changes the fraction of the output resolution that is rendered at, clamped to `(0, 1]`.
+    ///
+    /// Returns whether the render resolution changed.
+    pub fn set_render_scale(&mut self, render_scale: f32) -> bool {
+        self.render_scale = render_scale.clamp(f32::EPSILON, 1.0);
+        self.update_render_resolution()
+    }
+
+    fn update_render_resolution(&mut self) -> bool {
+        let resolution = (self.output_resolution.as_vec2() * self.render_scale)
+            .round()
+            .as_uvec2()
+            .max(glam::UVec2::ONE);
+        let resolution_changed = resolution != self.resolution;
+        self.resolution = resolution;
+        resolution_changed
+    }
+
+    /// Format of the surface, i.e. the display transform's output format.
     pub fn surface_format(&self) -> wgpu::TextureFormat {
         self.surface_format
     }
 
-    pub fn on_resize(&mut self, device: &wgpu::Device, new_resolution: glam::UVec2) {
-        self.configure_surface(device, new_resolution);
+    /// Present mode the surface was last configured with.
+    pub fn present_mode(&self) -> wgpu::PresentMode {
+        self.present_mode
+    }
+
+    /// Present modes the surface supports on this adapter, without the `Auto*` modes.
+    pub fn supported_present_modes(&self) -> &[wgpu::PresentMode] {
+        &self.supported_present_modes
+    }
+
+    /// Frame latency the surface was last configured with.
+    pub fn desired_maximum_frame_latency(&self) -> u32 {
+        self.desired_maximum_frame_latency
+    }
+
+    /// Changes the present mode, reconfiguring the surface if necessary.
+    ///
+    /// Modes that aren't supported by the surface are ignored with a warning.
+    /// The `Auto*` modes are always accepted since they have a guaranteed fallback.
+    pub fn set_present_mode(&mut self, device: &wgpu::Device, present_mode: wgpu::PresentMode) {
+        if present_mode == self.present_mode {
+            return;
+        }
+        let is_auto_mode = matches!(
+            present_mode,
+            wgpu::PresentMode::AutoVsync | wgpu::PresentMode::AutoNoVsync
+        );
+        if !is_auto_mode && !self.supported_present_modes.contains(&present_mode) {
+            log::warn!("Present mode {present_mode:?} is not supported by the surface.");
+            return;
+        }
+
+        self.present_mode = present_mode;
+        self.configure_surface(device);
+    }
+
+    /// Changes the desired maximum frame latency, reconfiguring the surface if necessary.
+    pub fn set_desired_maximum_frame_latency(&mut self, device: &wgpu::Device, latency: u32) {
+        let latency = latency.clamp(
+            *Self::FRAME_LATENCY_RANGE.start(),
+            *Self::FRAME_LATENCY_RANGE.end(),
+        );
+        if latency == self.desired_maximum_frame_latency {
+            return;
+        }
+
+        self.desired_maximum_frame_latency = latency;
+        self.configure_surface(device);
     }
 
+    /// Adapts to a new window size.
+    ///
+    /// Zero sized windows (e.g. minimized ones) are ignored, since lots of resources can't be zero sized.
+    /// Returns whether the render resolution changed.
+    pub fn on_resize(&mut self, device: &wgpu::Device, window_resolution: glam::UVec2) -> bool {
+        if window_resolution.x == 0
+            || window_resolution.y == 0
+            || window_resolution == self.window_resolution
+        {
+            return false;
+        }
+
+        let (surface_resolution, output_resolution) =
+            clamp_resolution(window_resolution, self.max_texture_dimension);
+        if output_resolution != window_resolution {
+            log::warn!(
+                "Window size {window_resolution} exceeds the maximum texture size of {}, rendering at {output_resolution} instead.",
+                self.max_texture_dimension
+            );
+        }
+
+        self.window_resolution = window_resolution;
+        self.surface_resolution = surface_resolution;
+        self.output_resolution = output_resolution;
+        self.configure_surface(device);
+
+        self.update_render_resolution()
+    }
+
+    /// Acquires the next surface texture to render to.
+    ///
+    /// Returns `None` if there's none this frame, e.g. because the surface was outdated and had to be reconfigured.
     pub fn start_frame(&mut self, device: &wgpu::Device) -> Option<wgpu::SurfaceTexture> {
         match self.surface.get_current_texture() {
             Ok(surface_texture) => Some(surface_texture),
@@ -55,7 +227,7 @@ impl<'a> Screen<'a> {
                     }
                     wgpu::SurfaceError::Outdated => {
                         // Need to reconfigure the surface and try again next frame.
-                        self.configure_surface(device, self.resolution);
+                        self.configure_surface(device);
                     }
                     wgpu::SurfaceError::Lost => {
                         log::error!("Swapchain has been lost.");
@@ -74,9 +246,8 @@ impl<'a> Screen<'a> {
         }
     }
 
-    fn configure_surface(&mut self, device: &wgpu::Device, new_resolution: glam::UVec2) {
-        self.resolution = new_resolution;
-        let (width, height) = new_resolution.into();
+    fn configure_surface(&mut self, device: &wgpu::Device) {
+        let (width, height) = self.surface_resolution.into();
         self.surface.configure(
             device,
             &wgpu::SurfaceConfiguration {
@@ -84,8 +255,8 @@ impl<'a> Screen<'a> {
                 usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
                 width,
                 height,
-                desired_maximum_frame_latency: 2,
-                present_mode: Self::PRESENT_MODE,
+                desired_maximum_frame_latency: self.desired_maximum_frame_latency,
+                present_mode: self.present_mode,
                 alpha_mode: wgpu::CompositeAlphaMode::Opaque,
                 view_formats: vec![],
             },
@@ -93,6 +264,28 @@ impl<'a> Screen<'a> {
     }
 }
 
+/// Clamps a window size to the maximum texture size.
+///
+/// Returns the surface resolution, clamped per axis, and the output resolution,
+/// which is scaled down uniformly to keep the aspect ratio of the window.
+fn clamp_resolution(
+    window_resolution: glam::UVec2,
+    max_texture_dimension: u32,
+) -> (glam::UVec2, glam::UVec2) {
+    let surface_resolution = window_resolution.min(glam::UVec2::splat(max_texture_dimension));
+    if surface_resolution == window_resolution {
+        return (surface_resolution, surface_resolution);
+    }
+
+    let scale = max_texture_dimension as f32 / window_resolution.max_element() as f32;
+    let resolution = (window_resolution.as_vec2() * scale)
+        .floor()
+        .as_uvec2()
+        .clamp(glam::UVec2::ONE, surface_resolution);
+
+    (surface_resolution, resolution)
+}
+
 fn pick_surface_format(surface: &wgpu::Surface, adapter: &wgpu::Adapter) -> wgpu::TextureFormat {
     // WebGPU doesn't support sRGB(-converting-on-write) output formats, but on native the first format is often an sRGB one.
     // So if we just blindly pick the first, we'll end up with different colors!
@@ -107,3 +300,67 @@ fn pick_surface_format(surface: &wgpu::Surface, adapter: &wgpu::Adapter) -> wgpu
     log::warn!("Couldn't find a non-sRGB format, defaulting to the first one");
     surface_capabilitites.formats[0]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::clamp_resolution;
+
+    const MAX: u32 = 8192;
+
+    #[test]
+    fn resolution_within_limits_is_unchanged() {
+        let window = glam::uvec2(1920, 1080);
+        assert_eq!(clamp_resolution(window, MAX), (window, window));
+    }
+
+    #[test]
+    fn width_too_large() {
+        let (surface, output) = clamp_resolution(glam::uvec2(10000, 1000), MAX);
+        assert_eq!(surface, glam::uvec2(MAX, 1000));
+        assert_eq!(output, glam::uvec2(MAX, 819));
+    }
+
+    #[test]
+    fn height_too_large() {
+        let (surface, output) = clamp_resolution(glam::uvec2(1000, 10000), MAX);
+        assert_eq!(surface, glam::uvec2(1000, MAX));
+        assert_eq!(output, glam::uvec2(819, MAX));
+    }
+
+    #[test]
+    fn both_too_large() {
+        let (surface, output) = clamp_resolution(glam::uvec2(16384, 9216), MAX);
+        assert_eq!(surface, glam::uvec2(MAX, MAX));
+        assert_eq!(output, glam::uvec2(MAX, 4608));
+    }
+
+    #[test]
+    fn aspect_ratio_is_kept() {
+        for window in [
+            glam::uvec2(12345, 6789),
+            glam::uvec2(6789, 12345),
+            glam::uvec2(30000, 20000),
+        ] {
+            let (surface, output) = clamp_resolution(window, MAX);
+            assert!(output.cmple(surface).all(), "{output} exceeds {surface}");
+
+            // Flooring loses less than a pixel on the shorter axis.
+            let window_aspect_ratio = window.x as f32 / window.y as f32;
+            let expected_y = output.x as f32 / window_aspect_ratio;
+            assert!(
+                (output.y as f32 - expected_y).abs() <= 1.0,
+                "{window} clamped to {output}"
+            );
+        }
+    }
+
+    #[test]
+    fn result_is_never_zero() {
+        let (surface, output) = clamp_resolution(glam::uvec2(100_000, 1), MAX);
+        assert_eq!(surface, glam::uvec2(MAX, 1));
+        assert_eq!(output, glam::uvec2(MAX, 1));
+
+        let (_, output) = clamp_resolution(glam::uvec2(1, 100_000), MAX);
+        assert_eq!(output, glam::uvec2(1, MAX));
+    }
+}
@@ -0,0 +1,130 @@
+//! Screenshots at a multiple of the window resolution.
+//!
+//! The scene is re-rendered offscreen at the screenshot resolution, read back and saved as PNG.
+//! Screenshots are limited to what fits into a single texture & readback buffer,
+//! tiling would require a camera projection that can be offset per tile.
+
+/// Scales screenshots can be taken at.
+pub const SCALE_RANGE: std::ops::RangeInclusive<u32> = 1..=8;
+
+/// Directory screenshots are saved to, relative to the working directory.
+#[cfg(not(target_arch = "wasm32"))]
+const SCREENSHOT_DIRECTORY: &str = "screenshots";
+
+/// Determines the resolution of a screenshot at `scale` times the window resolution.
+///
+/// The scale is reduced if the screenshot wouldn't fit into a single texture or readback buffer.
+pub fn screenshot_resolution(
+    limits: &wgpu::Limits,
+    window_resolution: glam::UVec2,
+    format: wgpu::TextureFormat,
+    scale: u32,
+) -> glam::UVec2 {
+    let bytes_per_pixel = format.block_copy_size(None).unwrap_or(4) as u64;
+    let fits = |scale: u32| {
+        let resolution = window_resolution * scale;
+        let padded_bytes_per_row = (resolution.x as u64 * bytes_per_pixel)
+            .next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT as u64);
+
+        resolution.max_element() <= limits.max_texture_dimension_2d
+            && padded_bytes_per_row * resolution.y as u64 <= limits.max_buffer_size
+    };
+
+    let requested_scale = scale.clamp(*SCALE_RANGE.start(), *SCALE_RANGE.end());
+    let scale = (1..=requested_scale)
+        .rev()
+        .find(|scale| fits(*scale))
+        .unwrap_or(1);
+    if scale != requested_scale {
+        log::warn!(
+            "Screenshot at {requested_scale}x window resolution exceeds device limits, using {scale}x instead."
+        );
+    }
+
+    window_resolution * scale
+}
+
+/// Creates a texture to render display transformed output into for readback.
+pub fn create_target(
+    device: &wgpu::Device,
+    label: &str,
+    resolution: glam::UVec2,
+    format: wgpu::TextureFormat,
+) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some(label),
+        size: wgpu::Extent3d {
+            width: resolution.x,
+            height: resolution.y,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    })
+}
+
+/// Saves tightly packed screenshot data as PNG on a background thread.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save(resolution: glam::UVec2, format: wgpu::TextureFormat, data: Vec<u8>) {
+    std::thread::spawn(move || match save_png(resolution, format, data) {
+        Ok(path) => log::info!("Saved screenshot to {path:?}"),
+        Err(err) => log::error!("Failed to save screenshot: {err:#}"),
+    });
+}
+
+/// Not supported on the web yet, only logs a warning.
+#[cfg(target_arch = "wasm32")]
+pub fn save(_resolution: glam::UVec2, _format: wgpu::TextureFormat, _data: Vec<u8>) {
+    log::warn!("Saving screenshots is not supported on the web yet.");
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn save_png(
+    resolution: glam::UVec2,
+    format: wgpu::TextureFormat,
+    data: Vec<u8>,
+) -> anyhow::Result<std::path::PathBuf> {
+    std::fs::create_dir_all(SCREENSHOT_DIRECTORY)?;
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_millis();
+    let path = std::path::Path::new(SCREENSHOT_DIRECTORY).join(format!(
+        "screenshot_{timestamp}_{}x{}.png",
+        resolution.x, resolution.y
+    ));
+    write_png(&path, resolution, format, data)?;
+
+    Ok(path)
+}
+
+/// Writes tightly packed 8 bit RGBA or BGRA data to a PNG file.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn write_png(
+    path: &std::path::Path,
+    resolution: glam::UVec2,
+    format: wgpu::TextureFormat,
+    mut data: Vec<u8>,
+) -> anyhow::Result<()> {
+    match format {
+        wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => {}
+        wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => {
+            for pixel in data.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+        }
+        _ => anyhow::bail!("Surface format {format:?} can't be saved as PNG"),
+    }
+
+    let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    let mut encoder = png::Encoder::new(file, resolution.x, resolution.y);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_source_srgb(png::SrgbRenderingIntent::Perceptual);
+    encoder.write_header()?.write_image_data(&data)?;
+
+    Ok(())
+}
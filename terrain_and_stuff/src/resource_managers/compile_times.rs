@@ -0,0 +1,56 @@
+use std::collections::HashMap;
+
+use web_time::Duration;
+
+/// Durations of all (re)compilations of a single shader module or pipeline.
+struct CompileTimeEntry {
+    num_compiles: u32,
+    total: Duration,
+    last: Duration,
+}
+
+/// Tracks how long shader modules or pipelines take to compile, including hot reloads.
+///
+/// Meant for finding out which shaders are blowing up startup & reload times.
+#[derive(Default)]
+pub struct CompileTimes {
+    entries: HashMap<String, CompileTimeEntry>,
+}
+
+impl CompileTimes {
+    pub fn record(&mut self, name: &str, duration: Duration) {
+        if let Some(entry) = self.entries.get_mut(name) {
+            entry.num_compiles += 1;
+            entry.total += duration;
+            entry.last = duration;
+        } else {
+            self.entries.insert(
+                name.to_owned(),
+                CompileTimeEntry {
+                    num_compiles: 1,
+                    total: duration,
+                    last: duration,
+                },
+            );
+        }
+    }
+
+    /// One line per entry, most expensive in total first.
+    pub fn report(&self) -> String {
+        let mut entries = self.entries.iter().collect::<Vec<_>>();
+        entries.sort_by_key(|(_, entry)| std::cmp::Reverse(entry.total));
+
+        entries
+            .into_iter()
+            .map(|(name, entry)| {
+                format!(
+                    "  {name}: last {:.2}ms, total {:.2}ms over {} compile(s)",
+                    entry.last.as_secs_f64() * 1000.0,
+                    entry.total.as_secs_f64() * 1000.0,
+                    entry.num_compiles,
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
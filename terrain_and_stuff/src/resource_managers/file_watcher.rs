@@ -0,0 +1,89 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::mpsc::Receiver,
+};
+
+use notify::event::{ModifyKind, RenameMode};
+
+/// Watches a directory recursively and sends the paths of all modified files to the returned receiver.
+///
+/// Files that are created or renamed into place count as modified as well,
+/// since editors & scripts often save by writing a temporary file and renaming it over the original.
+/// This means that paths of files that don't exist (anymore) or aren't fully written yet can be sent.
+///
+/// `what` is used for logging only, e.g. "shader".
+pub fn watch_directory(
+    directory: &Path,
+    what: &'static str,
+) -> notify::Result<(notify::RecommendedWatcher, Receiver<PathBuf>)> {
+    let (change_tx, change_rx) = std::sync::mpsc::channel();
+
+    let mut watcher =
+        notify::recommended_watcher(move |res: notify::Result<notify::Event>| match res {
+            Ok(event) => {
+                let changed_paths = match event.kind {
+                    notify::EventKind::Any
+                    | notify::EventKind::Create(_)
+                    | notify::EventKind::Modify(ModifyKind::Any)
+                    | notify::EventKind::Modify(ModifyKind::Data(_)) => event.paths.as_slice(),
+
+                    // Only the destination of a rename has new content, which is always the last path.
+                    notify::EventKind::Modify(ModifyKind::Name(
+                        RenameMode::To | RenameMode::Both | RenameMode::Any,
+                    )) => event
+                        .paths
+                        .last()
+                        .map(std::slice::from_ref)
+                        .unwrap_or_default(),
+
+                    notify::EventKind::Access(_)
+                    | notify::EventKind::Remove(_)
+                    | notify::EventKind::Other
+                    | notify::EventKind::Modify(_) => {
+                        // Reloading doesn't make sense?
+                        &[]
+                    }
+                };
+                for path in changed_paths {
+                    if let Err(err) = change_tx.send(path.clone()) {
+                        log::error!("Failed to send {what} change event: {}", err);
+                    }
+                }
+            }
+            Err(err) => log::error!("Failed to watch {what} directory: {}", err),
+        })?;
+
+    notify::Watcher::watch(&mut watcher, directory, notify::RecursiveMode::Recursive)?;
+
+    Ok((watcher, change_rx))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn file_renamed_into_place_is_reported() {
+        let directory = std::env::temp_dir().join(format!(
+            "terrain_and_stuff_file_watcher_test_{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("watched.txt");
+        std::fs::write(&path, "old").unwrap();
+
+        let (_watcher, change_rx) = watch_directory(&directory, "test").unwrap();
+
+        let temp_path = directory.join("watched.txt.tmp");
+        std::fs::write(&temp_path, "new").unwrap();
+        std::fs::rename(&temp_path, &path).unwrap();
+
+        let file_name = path.file_name();
+        let reported = std::iter::from_fn(|| change_rx.recv_timeout(Duration::from_secs(5)).ok())
+            .any(|changed_path| changed_path.file_name() == file_name);
+        std::fs::remove_dir_all(&directory).unwrap();
+        assert!(reported, "Rename over {path:?} wasn't reported");
+    }
+}
@@ -1,4 +1,19 @@
+//! Creation & hot reloading of GPU resources that originate from files: pipelines, shaders & textures.
+
+mod compile_times;
+#[cfg(not(target_arch = "wasm32"))]
+mod file_watcher;
+mod pipeline_layouts;
 mod pipelines;
 mod shader_cache;
+#[cfg(not(target_arch = "wasm32"))]
+mod shader_validation;
+mod texture_loader;
+mod texture_manager;
 
+#[cfg(not(target_arch = "wasm32"))]
+pub use file_watcher::watch_directory;
 pub use pipelines::*;
+#[cfg(not(target_arch = "wasm32"))]
+pub use shader_validation::validate_all_shaders;
+pub use texture_manager::TextureManager;
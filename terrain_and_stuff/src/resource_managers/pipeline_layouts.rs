@@ -0,0 +1,56 @@
+use std::collections::HashMap;
+
+use crate::wgpu_utils::BindGroupLayoutWithDesc;
+
+slotmap::new_key_type! { pub struct PipelineLayoutHandle; }
+
+/// Deduplicates pipeline layouts by the bind group layouts they're made of.
+///
+/// Bind group layouts with identical entries are interchangeable,
+/// so layouts are keyed by the entries of their bind group layouts rather than the layout objects.
+pub struct PipelineLayoutCache {
+    layouts: slotmap::SlotMap<PipelineLayoutHandle, wgpu::PipelineLayout>,
+    layouts_per_entries: HashMap<Vec<Vec<wgpu::BindGroupLayoutEntry>>, PipelineLayoutHandle>,
+}
+
+impl PipelineLayoutCache {
+    pub fn new() -> Self {
+        Self {
+            layouts: slotmap::SlotMap::default(),
+            layouts_per_entries: HashMap::default(),
+        }
+    }
+
+    /// Returns an existing pipeline layout with the same bind group layouts or creates a new one.
+    ///
+    /// The debug label is only used if a new layout is created.
+    pub fn get_or_create(
+        &mut self,
+        device: &wgpu::Device,
+        debug_label: &str,
+        bind_group_layouts: &[&BindGroupLayoutWithDesc],
+    ) -> PipelineLayoutHandle {
+        let key = bind_group_layouts
+            .iter()
+            .map(|layout| layout.entries.clone())
+            .collect::<Vec<_>>();
+
+        *self.layouts_per_entries.entry(key).or_insert_with(|| {
+            let bind_group_layouts = bind_group_layouts
+                .iter()
+                .map(|layout| &layout.layout)
+                .collect::<Vec<_>>();
+            self.layouts.insert(
+                device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                    label: Some(debug_label),
+                    bind_group_layouts: &bind_group_layouts,
+                    push_constant_ranges: &[],
+                }),
+            )
+        })
+    }
+
+    pub fn get(&self, handle: PipelineLayoutHandle) -> Option<&wgpu::PipelineLayout> {
+        self.layouts.get(handle)
+    }
+}
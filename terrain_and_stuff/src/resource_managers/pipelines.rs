@@ -1,14 +1,26 @@
 use std::{collections::HashSet, hash::Hash, path::PathBuf};
 
 use itertools::{self as _};
+use web_time::Instant;
+
+use super::{
+    compile_times::CompileTimes,
+    pipeline_layouts::{PipelineLayoutCache, PipelineLayoutHandle},
+    shader_cache::{ShaderCache, ShaderCacheError},
+};
+use crate::wgpu_utils::BindGroupLayoutWithDesc;
+
+slotmap::new_key_type! {
+    /// Handle to a render pipeline of a [`PipelineManager`], stays valid across shader reloads.
+    pub struct RenderPipelineHandle;
+}
+slotmap::new_key_type! {
+    /// Handle to a compute pipeline of a [`PipelineManager`], stays valid across shader reloads.
+    pub struct ComputePipelineHandle;
+}
 
-use super::shader_cache::{ShaderCache, ShaderCacheError};
-
-slotmap::new_key_type! { pub struct RenderPipelineHandle; }
-
-#[cfg(not(target_arch = "wasm32"))]
-const SHADERS_DIR: &str = "terrain_and_stuff/shaders";
-
+/// Shader file & function a pipeline stage is created from.
+#[derive(Clone)]
 pub struct ShaderEntryPoint {
     /// Path relative to the `shaders` directory.
     pub path: PathBuf,
@@ -32,32 +44,60 @@ impl ShaderEntryPoint {
 ///
 /// Also, leaving out some fields  that I don't need & simplifying others.
 /// (like vertex buffers. Srsly who needs vertex buffers in this time and day when you can just always do programmable pulling ;-))
+///
+/// Cheap to clone, so it's easy to create variants of a pipeline.
+#[derive(Clone)]
 pub struct RenderPipelineDescriptor {
+    /// Label of the pipeline, also used in logs.
     pub debug_label: String,
-    pub layout: wgpu::PipelineLayout, // TODO: pipeline layout sharing? Add a manager? Probably not that important.
+    /// Layout from [`PipelineManager::pipeline_layout`].
+    pub layout: PipelineLayoutHandle,
+    /// Vertex stage, vertices are pulled from bindings since there are no vertex buffers.
     pub vertex_shader: ShaderEntryPoint,
+    /// Fragment stage, there's always one.
     pub fragment_shader: ShaderEntryPoint,
+    /// Color targets written by the fragment shader.
     pub fragment_targets: Vec<wgpu::ColorTargetState>,
+    /// Same as [`wgpu::RenderPipelineDescriptor::primitive`].
     pub primitive: wgpu::PrimitiveState,
+    /// Same as [`wgpu::RenderPipelineDescriptor::depth_stencil`].
     pub depth_stencil: Option<wgpu::DepthStencilState>,
+    /// Same as [`wgpu::RenderPipelineDescriptor::multisample`].
     pub multisample: wgpu::MultisampleState,
 }
 
+/// Compute pipeline descriptor, the compute equivalent of [`RenderPipelineDescriptor`].
+#[derive(Clone)]
+pub struct ComputePipelineDescriptor {
+    /// Label of the pipeline, also used in logs.
+    pub debug_label: String,
+    /// Layout from [`PipelineManager::pipeline_layout`].
+    pub layout: PipelineLayoutHandle,
+    /// The only stage of the pipeline.
+    pub compute_shader: ShaderEntryPoint,
+}
+
 #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
-struct RenderPipelineEntry {
-    pipeline: wgpu::RenderPipeline,
-    descriptor: RenderPipelineDescriptor,
+struct PipelineEntry<Descriptor, Pipeline> {
+    pipeline: Pipeline,
+    descriptor: Descriptor,
 
-    /// List of all shader paths that went into building this render pipeline.
+    /// List of all shader paths that went into building this pipeline.
     dependent_shader_paths: HashSet<PathBuf>,
 }
 
+type RenderPipelineEntry = PipelineEntry<RenderPipelineDescriptor, wgpu::RenderPipeline>;
+type ComputePipelineEntry = PipelineEntry<ComputePipelineDescriptor, wgpu::ComputePipeline>;
+
+/// Errors creating pipelines or the pipeline manager itself.
 #[derive(thiserror::Error, Debug)]
 pub enum PipelineError {
+    /// Watching the shaders directory for changes failed.
     #[cfg(not(target_arch = "wasm32"))]
     #[error(transparent)]
     FileWatcherError(#[from] notify::Error),
 
+    /// A shader failed to load or compile.
     #[error(transparent)]
     ShaderLoadError(#[from] ShaderCacheError),
 }
@@ -67,72 +107,67 @@ pub enum PipelineError {
 /// Shaders are embedded in the binary on the web.
 pub struct PipelineManager {
     shader_cache: ShaderCache,
+    pipeline_layouts: PipelineLayoutCache,
     render_pipelines: slotmap::SlotMap<RenderPipelineHandle, RenderPipelineEntry>,
+    compute_pipelines: slotmap::SlotMap<ComputePipelineHandle, ComputePipelineEntry>,
+
+    /// Creation times per pipeline label, not including shader module compilation.
+    pipeline_creation_times: CompileTimes,
 
     #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
     shader_change_rx: std::sync::mpsc::Receiver<PathBuf>,
 
-    //compute_pipelines: slotmap::SlotMap<PipelineKey, wgpu::ComputePipeline>,
     #[cfg(not(target_arch = "wasm32"))]
     _filewatcher: notify::RecommendedWatcher,
 }
 
 impl PipelineManager {
+    /// Starts watching the shaders directory for changes on native.
     pub fn new() -> Result<Self, PipelineError> {
-        let (_shader_change_tx, shader_change_rx) = std::sync::mpsc::channel();
-
         #[cfg(not(target_arch = "wasm32"))]
-        let filewatcher = {
-            let mut watcher =
-                notify::recommended_watcher(move |res: notify::Result<notify::Event>| match res {
-                    Ok(event) => match event.kind {
-                        notify::EventKind::Any
-                        | notify::EventKind::Modify(notify::event::ModifyKind::Any)
-                        | notify::EventKind::Modify(notify::event::ModifyKind::Data(_)) => {
-                            for path in event.paths {
-                                if let Err(err) = _shader_change_tx.send(path) {
-                                    log::error!("Failed to send shader change event: {}", err);
-                                }
-                            }
-                        }
-
-                        notify::EventKind::Access(_)
-                        | notify::EventKind::Create(_)
-                        | notify::EventKind::Remove(_)
-                        | notify::EventKind::Other
-                        | notify::EventKind::Modify(_) => {
-                            // Reloading doesn't make sense?
-                        }
-                    },
-                    Err(err) => log::error!("Failed to watch shaders directory: {}", err),
-                })?;
-
-            notify::Watcher::watch(
-                &mut watcher,
-                std::path::Path::new(SHADERS_DIR),
-                notify::RecursiveMode::Recursive,
-            )?;
+        let (filewatcher, shader_change_rx) =
+            super::file_watcher::watch_directory(&crate::assets::shaders_dir(), "shader")?;
 
-            watcher
-        };
+        // There's no shader reloading on the web, so nothing is ever sent.
+        #[cfg(target_arch = "wasm32")]
+        let (_, shader_change_rx) = std::sync::mpsc::channel();
 
         Ok(Self {
             shader_cache: ShaderCache::new(),
+            pipeline_layouts: PipelineLayoutCache::new(),
             render_pipelines: slotmap::SlotMap::default(),
-            //compute_pipelines: slotmap::SlotMap::default(),
+            compute_pipelines: slotmap::SlotMap::default(),
+            pipeline_creation_times: CompileTimes::default(),
             shader_change_rx,
             #[cfg(not(target_arch = "wasm32"))]
             _filewatcher: filewatcher,
         })
     }
 
+    /// Returns a pipeline layout for the given bind group layouts, reusing an existing one if possible.
+    pub fn pipeline_layout(
+        &mut self,
+        device: &wgpu::Device,
+        debug_label: &str,
+        bind_group_layouts: &[&BindGroupLayoutWithDesc],
+    ) -> PipelineLayoutHandle {
+        self.pipeline_layouts
+            .get_or_create(device, debug_label, bind_group_layouts)
+    }
+
+    /// Loads all shaders of the descriptor & creates the pipeline, which is recreated whenever they change.
     pub fn create_render_pipeline(
         &mut self,
         device: &wgpu::Device,
         descriptor: RenderPipelineDescriptor,
     ) -> Result<RenderPipelineHandle, PipelineError> {
-        let (pipeline, dependent_shader_paths) =
-            create_wgpu_render_pipeline(&mut self.shader_cache, &descriptor, device)?;
+        let (pipeline, dependent_shader_paths) = create_wgpu_render_pipeline(
+            &mut self.shader_cache,
+            &self.pipeline_layouts,
+            &mut self.pipeline_creation_times,
+            &descriptor,
+            device,
+        )?;
         let handle = self.render_pipelines.insert(RenderPipelineEntry {
             pipeline,
             descriptor,
@@ -142,6 +177,29 @@ impl PipelineManager {
         Ok(handle)
     }
 
+    /// Loads the compute shader & creates the pipeline, which is recreated whenever the shader changes.
+    pub fn create_compute_pipeline(
+        &mut self,
+        device: &wgpu::Device,
+        descriptor: ComputePipelineDescriptor,
+    ) -> Result<ComputePipelineHandle, PipelineError> {
+        let (pipeline, dependent_shader_paths) = create_wgpu_compute_pipeline(
+            &mut self.shader_cache,
+            &self.pipeline_layouts,
+            &mut self.pipeline_creation_times,
+            &descriptor,
+            device,
+        )?;
+        let handle = self.compute_pipelines.insert(ComputePipelineEntry {
+            pipeline,
+            descriptor,
+            dependent_shader_paths,
+        });
+
+        Ok(handle)
+    }
+
+    /// Latest version of a render pipeline, `None` for handles of another manager.
     pub fn get_render_pipeline(
         &self,
         handle: RenderPipelineHandle,
@@ -151,14 +209,50 @@ impl PipelineManager {
             .map(|entry| &entry.pipeline)
     }
 
+    /// Descriptor a render pipeline was created with, useful for creating variants of it.
+    pub fn render_pipeline_descriptor(
+        &self,
+        handle: RenderPipelineHandle,
+    ) -> Option<&RenderPipelineDescriptor> {
+        self.render_pipelines
+            .get(handle)
+            .map(|entry| &entry.descriptor)
+    }
+
+    /// Latest version of a compute pipeline, `None` for handles of another manager.
+    pub fn get_compute_pipeline(
+        &self,
+        handle: ComputePipelineHandle,
+    ) -> Option<&wgpu::ComputePipeline> {
+        self.compute_pipelines
+            .get(handle)
+            .map(|entry| &entry.pipeline)
+    }
+
+    /// Logs how long each shader module & pipeline took to compile, including hot reloads.
+    pub fn log_compile_times(&self) {
+        log::info!(
+            "Shader module compile times:\n{}",
+            self.shader_cache.compile_times().report()
+        );
+        log::info!(
+            "Pipeline creation times (excluding shader modules):\n{}",
+            self.pipeline_creation_times.report()
+        );
+    }
+
+    /// Shaders can't change on the web.
     #[cfg(target_arch = "wasm32")]
     pub fn reload_changed_pipelines(&mut self, _device: &wgpu::Device) {}
 
+    /// Recreates all pipelines whose shaders changed on disk since the last call.
+    ///
+    /// Pipelines that fail to compile keep their previous version.
     #[cfg(not(target_arch = "wasm32"))]
     pub fn reload_changed_pipelines(&mut self, device: &wgpu::Device) {
         use itertools::Itertools as _;
 
-        let shader_base_path = std::path::Path::new(SHADERS_DIR).canonicalize().unwrap();
+        let shader_base_path = crate::assets::shaders_dir().canonicalize().unwrap();
 
         // Sometimes several change events come in at once, which is a bit annoying because of extra log.
         // Use `itertools::unique` to filter out duplicates.
@@ -175,29 +269,30 @@ impl PipelineManager {
             self.shader_cache.remove_shader_for_path(path);
 
             // Try to recreate all pipelines that use this shader.
-            for render_pipeline in self.render_pipelines.values_mut() {
-                if !render_pipeline.dependent_shader_paths.contains(path) {
-                    continue;
-                }
-
-                let label = &render_pipeline.descriptor.debug_label;
-                log::info!("Recreating pipeline {label:?}",);
-
-                match create_wgpu_render_pipeline(
-                    &mut self.shader_cache,
-                    &render_pipeline.descriptor,
-                    device,
-                ) {
-                    Ok((wgpu_pipeline, dependent_shader_paths)) => {
-                        render_pipeline.pipeline = wgpu_pipeline;
-                        render_pipeline.dependent_shader_paths = dependent_shader_paths;
-                    }
-                    Err(err) => {
-                        // This actually shouldn't happen since errors on pipeline creation itself are usually delayed.
-                        log::error!("Failed to recreate pipeline {label:?}: {err:?}");
-                        return; // Don't spam the user with errors for even more shaders.
-                    }
-                }
+            let all_recreated =
+                recreate_pipelines_using_shader(&mut self.render_pipelines, path, |descriptor| {
+                    create_wgpu_render_pipeline(
+                        &mut self.shader_cache,
+                        &self.pipeline_layouts,
+                        &mut self.pipeline_creation_times,
+                        descriptor,
+                        device,
+                    )
+                }) && recreate_pipelines_using_shader(
+                    &mut self.compute_pipelines,
+                    path,
+                    |descriptor| {
+                        create_wgpu_compute_pipeline(
+                            &mut self.shader_cache,
+                            &self.pipeline_layouts,
+                            &mut self.pipeline_creation_times,
+                            descriptor,
+                            device,
+                        )
+                    },
+                );
+            if !all_recreated {
+                return; // Don't spam the user with errors for even more shaders.
             }
 
             // TODO: remove dependent modules.
@@ -205,8 +300,60 @@ impl PipelineManager {
     }
 }
 
+/// Recreates all pipelines that depend on the given shader path.
+///
+/// Returns `false` if any pipeline failed to be recreated, in which case the remaining pipelines are skipped.
+#[cfg(not(target_arch = "wasm32"))]
+fn recreate_pipelines_using_shader<Handle: slotmap::Key, Descriptor: PipelineLabel, Pipeline>(
+    pipelines: &mut slotmap::SlotMap<Handle, PipelineEntry<Descriptor, Pipeline>>,
+    shader_path: &std::path::Path,
+    mut create_pipeline: impl FnMut(&Descriptor) -> Result<(Pipeline, HashSet<PathBuf>), PipelineError>,
+) -> bool {
+    for entry in pipelines.values_mut() {
+        if !entry.dependent_shader_paths.contains(shader_path) {
+            continue;
+        }
+
+        let label = entry.descriptor.debug_label();
+        log::info!("Recreating pipeline {label:?}",);
+
+        match create_pipeline(&entry.descriptor) {
+            Ok((pipeline, dependent_shader_paths)) => {
+                entry.pipeline = pipeline;
+                entry.dependent_shader_paths = dependent_shader_paths;
+            }
+            Err(err) => {
+                // This actually shouldn't happen since errors on pipeline creation itself are usually delayed.
+                log::error!("Failed to recreate pipeline {label:?}: {err:?}");
+                return false;
+            }
+        }
+    }
+
+    true
+}
+
+#[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+trait PipelineLabel {
+    fn debug_label(&self) -> &str;
+}
+
+impl PipelineLabel for RenderPipelineDescriptor {
+    fn debug_label(&self) -> &str {
+        &self.debug_label
+    }
+}
+
+impl PipelineLabel for ComputePipelineDescriptor {
+    fn debug_label(&self) -> &str {
+        &self.debug_label
+    }
+}
+
 fn create_wgpu_render_pipeline(
     shader_cache: &mut ShaderCache,
+    pipeline_layouts: &PipelineLayoutCache,
+    pipeline_creation_times: &mut CompileTimes,
     descriptor: &RenderPipelineDescriptor,
     device: &wgpu::Device,
 ) -> Result<(wgpu::RenderPipeline, HashSet<PathBuf>), PipelineError> {
@@ -231,9 +378,12 @@ fn create_wgpu_render_pipeline(
         .iter()
         .map(|target| Some(target.clone()))
         .collect::<Vec<_>>();
+    let layout = pipeline_layouts
+        .get(descriptor.layout)
+        .expect("Invalid pipeline layout handle");
     let wgpu_desc = wgpu::RenderPipelineDescriptor {
         label: Some(&descriptor.debug_label),
-        layout: Some(&descriptor.layout),
+        layout: Some(layout),
         vertex: wgpu::VertexState {
             module: &vertex_shader_module.module,
             entry_point: descriptor
@@ -260,10 +410,42 @@ fn create_wgpu_render_pipeline(
         multiview: None,
         cache: None,
     };
+    let creation_start = Instant::now();
     let pipeline = device.create_render_pipeline(&wgpu_desc);
+    pipeline_creation_times.record(&descriptor.debug_label, creation_start.elapsed());
     Ok((pipeline, dependent_shader_paths))
 }
 
+fn create_wgpu_compute_pipeline(
+    shader_cache: &mut ShaderCache,
+    pipeline_layouts: &PipelineLayoutCache,
+    pipeline_creation_times: &mut CompileTimes,
+    descriptor: &ComputePipelineDescriptor,
+    device: &wgpu::Device,
+) -> Result<(wgpu::ComputePipeline, HashSet<PathBuf>), PipelineError> {
+    let shader_handle =
+        shader_cache.get_or_load_shader_module(device, &descriptor.compute_shader.path)?;
+    let shader_module = shader_cache
+        .shader_module(shader_handle)
+        .expect("Invalid shader handle");
+
+    let layout = pipeline_layouts
+        .get(descriptor.layout)
+        .expect("Invalid pipeline layout handle");
+    let creation_start = Instant::now();
+    let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+        label: Some(&descriptor.debug_label),
+        layout: Some(layout),
+        module: &shader_module.module,
+        entry_point: descriptor.compute_shader.function_name.as_deref(),
+        compilation_options: pipeline_compilation_options(),
+        cache: None,
+    });
+    pipeline_creation_times.record(&descriptor.debug_label, creation_start.elapsed());
+
+    Ok((pipeline, shader_module.dependent_shaders.clone()))
+}
+
 fn pipeline_compilation_options() -> wgpu::PipelineCompilationOptions<'static> {
     wgpu::PipelineCompilationOptions::default()
 }
@@ -4,9 +4,9 @@ use std::{
 };
 
 use slotmap::{SecondaryMap, SlotMap};
+use web_time::Instant;
 
-#[cfg(not(target_arch = "wasm32"))]
-const SHADERS_DIR: &str = "terrain_and_stuff/shaders";
+use super::compile_times::CompileTimes;
 
 slotmap::new_key_type! { pub struct ShaderHandle; }
 
@@ -33,6 +33,9 @@ pub struct ShaderCache {
 
     // Once preprocessor setting is supported, a single path buf would map to several shaders?
     shader_sources_per_path: HashMap<PathBuf, ShaderHandle>,
+
+    /// Composition & module creation times per shader path.
+    compile_times: CompileTimes,
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -62,13 +65,15 @@ impl ShaderCache {
             shader_modules: Default::default(),
 
             shader_sources_per_path: Default::default(),
+
+            compile_times: CompileTimes::default(),
         }
     }
 
     /// Eareses all memory of a given shader path.
     ///
     /// This recursively removes all shaders depending on this path as well.
-    /// Path must be relative to [`SHADERS_DIR`].
+    /// Path must be relative to [`crate::assets::shaders_dir`].
     #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
     pub fn remove_shader_for_path(&mut self, path: &Path) {
         let Some(handle) = self.shader_sources_per_path.remove(path) else {
@@ -92,6 +97,10 @@ impl ShaderCache {
         self.shader_modules.get(handle)
     }
 
+    pub fn compile_times(&self) -> &CompileTimes {
+        &self.compile_times
+    }
+
     /// Get or load a shader module for the given path.
     ///
     /// If the shader module is already loaded, it will be returned.
@@ -111,22 +120,15 @@ impl ShaderCache {
             return Ok(handle);
         }
 
+        let compile_start = Instant::now();
+        let module = self.make_naga_module(handle)?;
         let source = &self.shader_sources[handle];
         let path = path.to_str().expect("Shader path is not valid UTF-8");
-
-        let module = self
-            .composer
-            .make_naga_module(naga_oil::compose::NagaModuleDescriptor {
-                source: &source.source,
-                file_path: path,
-                shader_type: naga_oil::compose::ShaderType::Wgsl,
-                shader_defs: HashMap::default(),
-                additional_imports: &[],
-            })?;
         let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some(path),
             source: wgpu::ShaderSource::Naga(std::borrow::Cow::Owned(module.to_owned())),
         });
+        self.compile_times.record(path, compile_start.elapsed());
 
         // Gather all dependent shaders.
         fn collect_dependent_shaders(
@@ -158,6 +160,46 @@ impl ShaderCache {
         Ok(handle)
     }
 
+    /// Loads, composes & validates the shader at the given path without creating a shader module.
+    ///
+    /// Returns a readable error with file & line information if anything fails.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn validate_shader(&mut self, path: &Path) -> Result<(), String> {
+        let result = self
+            .get_or_load_shader_source(path)
+            .and_then(|handle| self.make_naga_module(handle));
+
+        match result {
+            Ok(_) => Ok(()),
+            Err(ShaderCacheError::NagaOilComposeError(err)) => {
+                Err(err.emit_to_string(&self.composer))
+            }
+            Err(err) => Err(err.to_string()),
+        }
+    }
+
+    /// Composes a loaded shader source with all its imports into a validated naga module.
+    fn make_naga_module(
+        &mut self,
+        handle: ShaderHandle,
+    ) -> Result<wgpu::naga::Module, ShaderCacheError> {
+        let source = &self.shader_sources[handle];
+        let path = source
+            .file_path
+            .to_str()
+            .expect("Shader path is not valid UTF-8");
+
+        Ok(self
+            .composer
+            .make_naga_module(naga_oil::compose::NagaModuleDescriptor {
+                source: &source.source,
+                file_path: path,
+                shader_type: naga_oil::compose::ShaderType::Wgsl,
+                shader_defs: HashMap::default(),
+                additional_imports: &[],
+            })?)
+    }
+
     /// Loads shader source into the composer and returns a handle if it wasn't already loaded.
     fn get_or_load_shader_source(&mut self, path: &Path) -> Result<ShaderHandle, ShaderCacheError> {
         if let Some(handle) = self.shader_sources_per_path.get(path) {
@@ -207,6 +249,10 @@ impl ShaderCache {
 }
 
 fn raw_shader_source(path: &std::path::Path) -> Result<String, ShaderCacheError> {
+    if path == Path::new(crate::shader_interop::GENERATED_SHADER_PATH) {
+        return Ok(crate::shader_interop::generated_shader_source());
+    }
+
     #[cfg(target_arch = "wasm32")]
     {
         let path_str = path.to_str().unwrap();
@@ -226,7 +272,7 @@ fn raw_shader_source(path: &std::path::Path) -> Result<String, ShaderCacheError>
     }
     #[cfg(not(target_arch = "wasm32"))]
     {
-        let path = std::path::Path::new(SHADERS_DIR).join(path);
+        let path = crate::assets::shaders_dir().join(path);
         Ok(std::fs::read_to_string(&path).map_err(|err| {
             ShaderCacheError::FailedToLoadShaderSource {
                 path: path.to_path_buf(),
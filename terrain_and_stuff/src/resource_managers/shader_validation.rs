@@ -0,0 +1,74 @@
+use std::path::{Path, PathBuf};
+
+use super::shader_cache::ShaderCache;
+
+#[derive(thiserror::Error, Debug)]
+pub enum ShaderValidationError {
+    #[error("Failed to list shaders in {path:?}: {err}")]
+    FailedToListShaders { path: PathBuf, err: std::io::Error },
+
+    #[error("{num_failed} of {num_shaders} shaders failed validation")]
+    ValidationFailed {
+        num_failed: usize,
+        num_shaders: usize,
+    },
+}
+
+/// Composes & validates every shader in the shaders directory, logging all errors.
+///
+/// On the web, shaders are embedded and only compiled at runtime,
+/// so this is run before web builds to catch broken shaders early.
+/// Shaders are validated with default naga capabilities & without any shader defs, same as at runtime.
+pub fn validate_all_shaders() -> Result<(), ShaderValidationError> {
+    let shaders_dir = crate::assets::shaders_dir();
+    let mut shader_paths = Vec::new();
+    collect_shader_paths(&shaders_dir, Path::new(""), &mut shader_paths)?;
+    shader_paths.sort();
+    shader_paths.push(PathBuf::from(crate::shader_interop::GENERATED_SHADER_PATH));
+
+    let mut shader_cache = ShaderCache::new();
+    let mut num_failed = 0;
+    for path in &shader_paths {
+        match shader_cache.validate_shader(path) {
+            Ok(()) => log::info!("{path:?}: ok"),
+            Err(err) => {
+                log::error!("{path:?}:\n{err}");
+                num_failed += 1;
+            }
+        }
+    }
+
+    if num_failed > 0 {
+        Err(ShaderValidationError::ValidationFailed {
+            num_failed,
+            num_shaders: shader_paths.len(),
+        })
+    } else {
+        Ok(())
+    }
+}
+
+/// Gathers all `.wgsl` files in a directory recursively, as paths relative to the shaders directory.
+fn collect_shader_paths(
+    shaders_dir: &Path,
+    relative_dir: &Path,
+    shader_paths: &mut Vec<PathBuf>,
+) -> Result<(), ShaderValidationError> {
+    let dir = shaders_dir.join(relative_dir);
+    let entries =
+        std::fs::read_dir(&dir).map_err(|err| ShaderValidationError::FailedToListShaders {
+            path: dir.clone(),
+            err,
+        })?;
+
+    for entry in entries.flatten() {
+        let relative_path = relative_dir.join(entry.file_name());
+        if entry.path().is_dir() {
+            collect_shader_paths(shaders_dir, &relative_path, shader_paths)?;
+        } else if relative_path.extension().is_some_and(|ext| ext == "wgsl") {
+            shader_paths.push(relative_path);
+        }
+    }
+
+    Ok(())
+}
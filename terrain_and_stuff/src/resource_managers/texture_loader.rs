@@ -0,0 +1,173 @@
+//! Loading of textures from container formats.
+
+use crate::wgpu_utils::GpuCapabilities;
+
+#[derive(thiserror::Error, Debug)]
+pub enum TextureLoadError {
+    #[error(transparent)]
+    Ktx2ParseError(#[from] ktx2::ParseError),
+
+    #[error("KTX2 texture doesn't specify a format (Basis Universal textures are not supported)")]
+    MissingFormat,
+
+    #[error("Texture format {0:?} is not supported")]
+    UnsupportedFormat(ktx2::Format),
+
+    #[error("KTX2 supercompression scheme {0:?} is not supported")]
+    UnsupportedSupercompression(ktx2::SupercompressionScheme),
+
+    #[error("Only plain 2D textures are supported, no arrays, cube maps or 3D textures")]
+    UnsupportedDimension,
+
+    #[error("Texture size {width}x{height} is not a multiple of the block size of {format:?}")]
+    SizeNotBlockAligned {
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+    },
+
+    #[error("Failed to decode block compressed texture data: {0}")]
+    DecodeError(&'static str),
+}
+
+/// Loads a 2D texture with all its mips from a KTX2 container.
+///
+/// Block compressed data (BC5 & BC7) is uploaded directly if the device supports BC texture compression.
+/// Otherwise, it is decoded on the CPU to an uncompressed 8bit format.
+/// This is mostly relevant for WebGPU where BC support isn't guaranteed.
+pub fn load_ktx2_texture(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    capabilities: &GpuCapabilities,
+    ktx2_data: &[u8],
+    label: &str,
+) -> Result<wgpu::Texture, TextureLoadError> {
+    let reader = ktx2::Reader::new(ktx2_data)?;
+    let header = reader.header();
+
+    if let Some(scheme) = header.supercompression_scheme {
+        return Err(TextureLoadError::UnsupportedSupercompression(scheme));
+    }
+    if header.pixel_depth > 1 || header.layer_count > 1 || header.face_count > 1 {
+        return Err(TextureLoadError::UnsupportedDimension);
+    }
+
+    let ktx2_format = header.format.ok_or(TextureLoadError::MissingFormat)?;
+    let stored_format = wgpu_format_from_ktx2(ktx2_format)?;
+    let upload_format = if stored_format.is_compressed() && !capabilities.texture_compression_bc {
+        log::info!(
+            "Block compression not supported, decoding {label:?} ({stored_format:?}) on the CPU."
+        );
+        decoded_format(stored_format).ok_or(TextureLoadError::UnsupportedFormat(ktx2_format))?
+    } else {
+        stored_format
+    };
+
+    let (block_width, block_height) = stored_format.block_dimensions();
+    if header.pixel_width % block_width != 0 || header.pixel_height.max(1) % block_height != 0 {
+        return Err(TextureLoadError::SizeNotBlockAligned {
+            width: header.pixel_width,
+            height: header.pixel_height,
+            format: stored_format,
+        });
+    }
+
+    let size = wgpu::Extent3d {
+        width: header.pixel_width,
+        height: header.pixel_height.max(1),
+        depth_or_array_layers: 1,
+    };
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some(label),
+        size,
+        mip_level_count: header.level_count.max(1),
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: upload_format,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        view_formats: &[],
+    });
+
+    for (mip_level, level) in reader.levels().enumerate() {
+        let mip_level = mip_level as u32;
+        let mip_size = size.mip_level_size(mip_level, wgpu::TextureDimension::D2);
+
+        let decoded;
+        let data = if upload_format == stored_format {
+            level.data
+        } else {
+            decoded = decode_block_compressed(stored_format, mip_size, level.data)?;
+            &decoded
+        };
+
+        let physical_size = mip_size.physical_size(upload_format);
+        let (block_width, block_height) = upload_format.block_dimensions();
+        let block_size = upload_format
+            .block_copy_size(None)
+            .expect("Upload format is expected to have a single aspect");
+
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &texture,
+                mip_level,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            data,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(physical_size.width / block_width * block_size),
+                rows_per_image: Some(physical_size.height / block_height),
+            },
+            physical_size,
+        );
+    }
+
+    Ok(texture)
+}
+
+fn wgpu_format_from_ktx2(format: ktx2::Format) -> Result<wgpu::TextureFormat, TextureLoadError> {
+    Ok(match format {
+        ktx2::Format::BC7_UNORM_BLOCK => wgpu::TextureFormat::Bc7RgbaUnorm,
+        ktx2::Format::BC7_SRGB_BLOCK => wgpu::TextureFormat::Bc7RgbaUnormSrgb,
+        ktx2::Format::BC5_UNORM_BLOCK => wgpu::TextureFormat::Bc5RgUnorm,
+        ktx2::Format::R8G8B8A8_UNORM => wgpu::TextureFormat::Rgba8Unorm,
+        ktx2::Format::R8G8B8A8_SRGB => wgpu::TextureFormat::Rgba8UnormSrgb,
+        _ => return Err(TextureLoadError::UnsupportedFormat(format)),
+    })
+}
+
+/// Format that block compressed data is decoded to if block compression isn't available.
+fn decoded_format(format: wgpu::TextureFormat) -> Option<wgpu::TextureFormat> {
+    // `texture2ddecoder` writes BGRA8 pixels.
+    match format {
+        wgpu::TextureFormat::Bc7RgbaUnorm | wgpu::TextureFormat::Bc5RgUnorm => {
+            Some(wgpu::TextureFormat::Bgra8Unorm)
+        }
+        wgpu::TextureFormat::Bc7RgbaUnormSrgb => Some(wgpu::TextureFormat::Bgra8UnormSrgb),
+        _ => None,
+    }
+}
+
+fn decode_block_compressed(
+    format: wgpu::TextureFormat,
+    size: wgpu::Extent3d,
+    data: &[u8],
+) -> Result<Vec<u8>, TextureLoadError> {
+    let width = size.width as usize;
+    let height = size.height as usize;
+    let mut pixels = vec![0_u32; width * height];
+
+    match format {
+        wgpu::TextureFormat::Bc7RgbaUnorm | wgpu::TextureFormat::Bc7RgbaUnormSrgb => {
+            texture2ddecoder::decode_bc7(data, width, height, &mut pixels)
+        }
+        wgpu::TextureFormat::Bc5RgUnorm => {
+            texture2ddecoder::decode_bc5(data, width, height, &mut pixels)
+        }
+        _ => unreachable!("No decoder for {format:?}"),
+    }
+    .map_err(TextureLoadError::DecodeError)?;
+
+    Ok(pixels.into_iter().flat_map(u32::to_le_bytes).collect())
+}
@@ -0,0 +1,225 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use super::texture_loader::{load_ktx2_texture, TextureLoadError};
+use crate::wgpu_utils::GpuCapabilities;
+
+slotmap::new_key_type! { pub struct TextureHandle; }
+
+struct TextureEntry {
+    label: String,
+
+    /// Path relative to the assets directory.
+    path: PathBuf,
+
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum TextureManagerError {
+    #[cfg(not(target_arch = "wasm32"))]
+    #[error(transparent)]
+    FileWatcherError(#[from] notify::Error),
+
+    #[error(transparent)]
+    AssetError(#[from] crate::assets::AssetError),
+
+    #[error("Unsupported texture file type {path:?}, expected a .ktx2 file")]
+    UnsupportedFileType { path: PathBuf },
+
+    #[error("Failed to load texture {path:?}: {err}")]
+    LoadError {
+        path: PathBuf,
+        err: TextureLoadError,
+    },
+}
+
+/// Texture manager with simple texture reload (native only).
+///
+/// Textures are embedded in the binary on the web.
+/// Textures are identified by handles and can be looked up by label.
+/// Whenever a texture is reloaded, its handle stays the same, but the underlying texture & view change,
+/// so anything that holds on to views (e.g. bind groups) needs to be recreated.
+pub struct TextureManager {
+    textures: slotmap::SlotMap<TextureHandle, TextureEntry>,
+    textures_per_label: HashMap<String, TextureHandle>,
+    capabilities: GpuCapabilities,
+
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+    texture_change_rx: std::sync::mpsc::Receiver<PathBuf>,
+
+    #[cfg(not(target_arch = "wasm32"))]
+    _filewatcher: Option<notify::RecommendedWatcher>,
+}
+
+impl TextureManager {
+    /// Starts watching the assets directory for changes on native, if there is one.
+    pub fn new(capabilities: GpuCapabilities) -> Result<Self, TextureManagerError> {
+        #[cfg(not(target_arch = "wasm32"))]
+        let (filewatcher, texture_change_rx) = {
+            let assets_dir = crate::assets::assets_dir();
+            if assets_dir.exists() {
+                let (filewatcher, texture_change_rx) =
+                    super::file_watcher::watch_directory(&assets_dir, "texture")?;
+                (Some(filewatcher), texture_change_rx)
+            } else {
+                log::info!(
+                    "Assets directory {assets_dir:?} doesn't exist, texture reloading is disabled."
+                );
+                (None, std::sync::mpsc::channel().1)
+            }
+        };
+
+        // There's no texture reloading on the web, so nothing is ever sent.
+        #[cfg(target_arch = "wasm32")]
+        let (_, texture_change_rx) = std::sync::mpsc::channel();
+
+        Ok(Self {
+            textures: slotmap::SlotMap::default(),
+            textures_per_label: HashMap::default(),
+            capabilities,
+            texture_change_rx,
+            #[cfg(not(target_arch = "wasm32"))]
+            _filewatcher: filewatcher,
+        })
+    }
+
+    /// Loads a texture from a file path relative to the assets directory.
+    ///
+    /// If a texture with the same label was already loaded, its handle is returned instead.
+    pub fn load_texture(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        label: &str,
+        path: impl Into<PathBuf>,
+    ) -> Result<TextureHandle, TextureManagerError> {
+        if let Some(handle) = self.textures_per_label.get(label) {
+            return Ok(*handle);
+        }
+
+        let path = path.into();
+        let texture = load_texture_from_file(device, queue, &self.capabilities, label, &path)?;
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let handle = self.textures.insert(TextureEntry {
+            label: label.to_owned(),
+            path,
+            texture,
+            view,
+        });
+        self.textures_per_label.insert(label.to_owned(), handle);
+
+        Ok(handle)
+    }
+
+    /// Handle of a texture that was loaded with the given label.
+    pub fn handle_for_label(&self, label: &str) -> Option<TextureHandle> {
+        self.textures_per_label.get(label).copied()
+    }
+
+    /// Latest version of a texture, `None` for handles of another manager.
+    pub fn texture(&self, handle: TextureHandle) -> Option<&wgpu::Texture> {
+        self.textures.get(handle).map(|entry| &entry.texture)
+    }
+
+    /// View of the latest version of a texture, `None` for handles of another manager.
+    pub fn texture_view(&self, handle: TextureHandle) -> Option<&wgpu::TextureView> {
+        self.textures.get(handle).map(|entry| &entry.view)
+    }
+
+    /// Reloads all textures whose files changed on disk.
+    ///
+    /// Returns the handles of all reloaded textures, so users can recreate dependent bind groups.
+    #[cfg(target_arch = "wasm32")]
+    pub fn reload_changed_textures(
+        &mut self,
+        _device: &wgpu::Device,
+        _queue: &wgpu::Queue,
+    ) -> Vec<TextureHandle> {
+        Vec::new()
+    }
+
+    /// Reloads all textures whose files changed on disk.
+    ///
+    /// Returns the handles of all reloaded textures, so users can recreate dependent bind groups.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn reload_changed_textures(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> Vec<TextureHandle> {
+        use itertools::Itertools as _;
+
+        let mut reloaded = Vec::new();
+
+        let Ok(textures_base_path) = crate::assets::assets_dir().canonicalize() else {
+            return reloaded;
+        };
+
+        // Sometimes several change events come in at once, use `itertools::unique` to filter out duplicates.
+        for path in self.texture_change_rx.try_iter().unique() {
+            let Ok(path) = path.canonicalize() else {
+                continue;
+            };
+            let Ok(path) = path.strip_prefix(&textures_base_path) else {
+                continue;
+            };
+
+            for (handle, entry) in &mut self.textures {
+                if entry.path != path {
+                    continue;
+                }
+
+                log::info!("Reloading texture {:?}", entry.label);
+
+                match load_texture_from_file(
+                    device,
+                    queue,
+                    &self.capabilities,
+                    &entry.label,
+                    &entry.path,
+                ) {
+                    Ok(texture) => {
+                        entry.view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+                        entry.texture = texture;
+                        reloaded.push(handle);
+                    }
+                    Err(err) => {
+                        log::error!("Failed to reload texture {:?}: {err}", entry.label);
+                    }
+                }
+            }
+        }
+
+        reloaded
+    }
+}
+
+fn load_texture_from_file(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    capabilities: &GpuCapabilities,
+    label: &str,
+    path: &Path,
+) -> Result<wgpu::Texture, TextureManagerError> {
+    let is_ktx2 = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("ktx2"));
+    if !is_ktx2 {
+        return Err(TextureManagerError::UnsupportedFileType {
+            path: path.to_path_buf(),
+        });
+    }
+
+    let data = crate::assets::load_asset(path)?;
+    load_ktx2_texture(device, queue, capabilities, &data, label).map_err(|err| {
+        TextureManagerError::LoadError {
+            path: path.to_path_buf(),
+            err,
+        }
+    })
+}
@@ -0,0 +1,161 @@
+//! Types shared between Rust & WGSL.
+//!
+//! Instead of mirroring structs by hand, they're declared once via `wgsl_struct!` and their WGSL definitions
+//! are served to the shaders as the virtual file [`GENERATED_SHADER_PATH`].
+//! Shaders import from it like from any other file, e.g. `#import "generated.wgsl"::FrameUniformBuffer`.
+
+/// Path of the generated shader file, relative to the shaders directory.
+///
+/// There's no such file on disk, the shader cache serves [`generated_shader_source`] instead.
+pub const GENERATED_SHADER_PATH: &str = "generated.wgsl";
+
+/// WGSL source with all shared structs & constants.
+pub fn generated_shader_source() -> String {
+    WgslSourceBuilder::default()
+        .struct_definition::<crate::global_bindings::FrameUniformBuffer>()
+        .finish()
+}
+
+/// Rust types with a WGSL equivalent that can be used in shared structs.
+pub trait WgslType {
+    /// Name of the type in WGSL.
+    const NAME: &'static str;
+
+    /// Alignment in the uniform & storage address spaces.
+    const ALIGN: usize;
+
+    /// Size in bytes, same in Rust & WGSL.
+    const SIZE: usize;
+}
+
+macro_rules! impl_wgsl_type {
+    ($ty:ty, $name:literal, $align:literal) => {
+        impl WgslType for $ty {
+            const NAME: &'static str = $name;
+            const ALIGN: usize = $align;
+            const SIZE: usize = std::mem::size_of::<$ty>();
+        }
+    };
+}
+
+impl_wgsl_type!(f32, "f32", 4);
+impl_wgsl_type!(u32, "u32", 4);
+impl_wgsl_type!(i32, "i32", 4);
+impl_wgsl_type!([f32; 2], "vec2f", 8);
+impl_wgsl_type!([u32; 2], "vec2u", 8);
+impl_wgsl_type!([i32; 2], "vec2i", 8);
+impl_wgsl_type!([f32; 4], "vec4f", 16);
+impl_wgsl_type!([u32; 4], "vec4u", 16);
+impl_wgsl_type!([i32; 4], "vec4i", 16);
+impl_wgsl_type!([[f32; 4]; 4], "mat4x4f", 16);
+
+/// A struct declared via `wgsl_struct!`.
+pub trait WgslStruct {
+    /// The struct definition in WGSL.
+    fn wgsl_definition() -> String;
+}
+
+/// Declares a `#[repr(C)]` POD struct along with its WGSL definition.
+///
+/// Field types have to implement [`WgslType`].
+/// Fails to compile if the Rust layout differs from the WGSL layout,
+/// add explicit padding fields where WGSL alignment rules require it.
+/// Field doc comments are carried over as WGSL comments.
+macro_rules! wgsl_struct {
+    (
+        $(#[doc = $struct_doc:literal])*
+        pub struct $name:ident {
+            $(
+                $(#[doc = $field_doc:literal])*
+                pub $field:ident: $ty:ty,
+            )*
+        }
+    ) => {
+        $(#[doc = $struct_doc])*
+        #[repr(C)]
+        #[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+        pub struct $name {
+            $(
+                $(#[doc = $field_doc])*
+                pub $field: $ty,
+            )*
+        }
+
+        impl $crate::shader_interop::WgslStruct for $name {
+            fn wgsl_definition() -> String {
+                let mut definition = format!("struct {} {{\n", stringify!($name));
+                $(
+                    $(
+                        definition.push_str(&format!("    //{}\n", $field_doc));
+                    )*
+                    definition.push_str(&format!(
+                        "    {}: {},\n",
+                        stringify!($field),
+                        <$ty as $crate::shader_interop::WgslType>::NAME
+                    ));
+                )*
+                definition.push_str("}\n");
+                definition
+            }
+        }
+
+        // Check that the Rust layout matches the WGSL layout.
+        const _: () = {
+            use $crate::shader_interop::WgslType;
+
+            let mut wgsl_offset: usize = 0;
+            let mut wgsl_align: usize = 1;
+            $(
+                wgsl_offset = wgsl_offset.next_multiple_of(<$ty>::ALIGN);
+                assert!(
+                    std::mem::offset_of!($name, $field) == wgsl_offset,
+                    concat!("Field `", stringify!($field), "` has a different offset in WGSL")
+                );
+                wgsl_offset += <$ty>::SIZE;
+                if <$ty>::ALIGN > wgsl_align {
+                    wgsl_align = <$ty>::ALIGN;
+                }
+            )*
+            assert!(
+                std::mem::size_of::<$name>() == wgsl_offset.next_multiple_of(wgsl_align),
+                concat!("`", stringify!($name), "` has a different size in WGSL")
+            );
+        };
+    };
+}
+pub(crate) use wgsl_struct;
+
+/// Assembles the generated shader file.
+#[derive(Default)]
+struct WgslSourceBuilder {
+    source: String,
+}
+
+impl WgslSourceBuilder {
+    #[allow(dead_code)] // No shared constants yet.
+    fn constant_u32(mut self, name: &str, value: u32) -> Self {
+        self.source
+            .push_str(&format!("const {name}: u32 = {value}u;\n\n"));
+        self
+    }
+
+    #[allow(dead_code)] // No shared constants yet.
+    fn constant_f32(mut self, name: &str, value: f32) -> Self {
+        self.source
+            .push_str(&format!("const {name}: f32 = {value:?};\n\n"));
+        self
+    }
+
+    fn struct_definition<T: WgslStruct>(mut self) -> Self {
+        self.source.push_str(&T::wgsl_definition());
+        self.source.push('\n');
+        self
+    }
+
+    fn finish(self) -> String {
+        format!(
+            "// Generated from Rust types in `shader_interop.rs`, do not edit.\n\n{}",
+            self.source
+        )
+    }
+}
@@ -1,4 +1,7 @@
+//! Sky pass, filling the background of the HDR backbuffer.
+
 use crate::{
+    global_bindings::GlobalBindings,
     render_output::HdrBackbuffer,
     resource_managers::{
         PipelineError, PipelineManager, RenderPipelineDescriptor, RenderPipelineHandle,
@@ -6,20 +9,20 @@ use crate::{
     },
 };
 
+/// Procedural sky drawn as a screen filling triangle.
 pub struct Sky {
     render_pipeline: RenderPipelineHandle,
 }
 
 impl Sky {
+    /// Creates the sky pipeline, rendering into the HDR backbuffer with the global bindings.
     pub fn new(
         device: &wgpu::Device,
         pipeline_manager: &mut PipelineManager,
+        global_bindings: &GlobalBindings,
     ) -> Result<Self, PipelineError> {
-        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-            label: Some("Sky"),
-            bind_group_layouts: &[],
-            push_constant_ranges: &[],
-        });
+        let layout =
+            pipeline_manager.pipeline_layout(device, "Sky", &[global_bindings.bind_group_layout()]);
 
         let render_pipeline = pipeline_manager.create_render_pipeline(
             device,
@@ -38,6 +41,9 @@ impl Sky {
         Ok(Self { render_pipeline })
     }
 
+    /// Draws the sky into a pass on the HDR backbuffer with the global bindings set.
+    ///
+    /// Returns `None` if the pipeline isn't available.
     pub fn draw<'a>(
         &self,
         rpass: &mut wgpu::RenderPass<'a>,
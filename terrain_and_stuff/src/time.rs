@@ -0,0 +1,156 @@
+use std::time::Duration;
+
+use web_time::Instant;
+
+/// Keeps track of real (wall clock) time and simulation time.
+///
+/// Rendering should use the real frame delta, whereas anything that simulates (erosion, water, day cycle, ...)
+/// should use the simulation clock which can be paused, slowed down, sped up and scrubbed.
+/// Simulation time only ever accumulates unless explicitly scrubbed.
+pub struct Time {
+    last_frame_instant: Instant,
+
+    /// Real time that passed between the last two frames.
+    real_delta: Duration,
+
+    /// Accumulated simulation time.
+    simulation_time: Duration,
+
+    /// Simulation time that passed between the last two frames.
+    simulation_delta: Duration,
+
+    /// Factor by which simulation time advances relative to real time.
+    time_scale: f32,
+    paused: bool,
+
+    /// If set, simulation steps are taken in fixed increments of this duration.
+    fixed_timestep: Option<Duration>,
+    fixed_timestep_accumulator: Duration,
+}
+
+impl Time {
+    /// Maximum real time delta a single frame may contribute.
+    ///
+    /// Avoids huge jumps after stalls, e.g. when sitting on a breakpoint or dragging the window.
+    const MAX_FRAME_DELTA: Duration = Duration::from_millis(250);
+
+    /// Maximum number of fixed timesteps taken per frame, protecting against the "spiral of death"
+    /// where simulation can't keep up with real time.
+    const MAX_FIXED_STEPS_PER_FRAME: u32 = 8;
+
+    pub const TIME_SCALE_RANGE: std::ops::RangeInclusive<f32> = (1.0 / 64.0)..=64.0;
+
+    pub fn new() -> Self {
+        Self {
+            last_frame_instant: Instant::now(),
+            real_delta: Duration::ZERO,
+            simulation_time: Duration::ZERO,
+            simulation_delta: Duration::ZERO,
+            time_scale: 1.0,
+            paused: false,
+            fixed_timestep: None,
+            fixed_timestep_accumulator: Duration::ZERO,
+        }
+    }
+
+    /// Measured real time since the last call to [`Time::advance_frame`].
+    pub fn time_since_last_frame(&self) -> Duration {
+        self.last_frame_instant.elapsed()
+    }
+
+    /// Advances all clocks. Should be called exactly once at the start of every frame.
+    ///
+    /// `real_delta` is usually [`Time::time_since_last_frame`], but may come from an input replay.
+    pub fn advance_frame(&mut self, real_delta: Duration) {
+        self.real_delta = real_delta.min(Self::MAX_FRAME_DELTA);
+        self.last_frame_instant = Instant::now();
+
+        self.simulation_delta = if self.paused {
+            Duration::ZERO
+        } else {
+            self.real_delta.mul_f32(self.time_scale)
+        };
+        self.simulation_time += self.simulation_delta;
+
+        if let Some(fixed_timestep) = self.fixed_timestep {
+            self.fixed_timestep_accumulator += self.simulation_delta;
+            self.fixed_timestep_accumulator = self
+                .fixed_timestep_accumulator
+                .min(fixed_timestep * Self::MAX_FIXED_STEPS_PER_FRAME);
+        }
+    }
+
+    /// Real time that passed between the last two frames.
+    ///
+    /// Unlike a frame limiter's target delta, this is the actually measured duration.
+    pub fn real_delta(&self) -> Duration {
+        self.real_delta
+    }
+
+    /// Accumulated simulation time.
+    pub fn simulation_time(&self) -> Duration {
+        self.simulation_time
+    }
+
+    /// Simulation time that passed between the last two frames.
+    ///
+    /// Zero if paused.
+    pub fn simulation_delta(&self) -> Duration {
+        self.simulation_delta
+    }
+
+    /// Takes the next fixed simulation step if enough simulation time has accumulated.
+    ///
+    /// Use in a loop: `while let Some(dt) = time.take_fixed_step() { simulate(dt); }`
+    /// Returns `None` right away if no fixed timestep is set.
+    #[allow(dead_code)] // Nothing steps a simulation yet.
+    pub fn take_fixed_step(&mut self) -> Option<Duration> {
+        let fixed_timestep = self.fixed_timestep?;
+        if self.fixed_timestep_accumulator < fixed_timestep {
+            return None;
+        }
+        self.fixed_timestep_accumulator -= fixed_timestep;
+        Some(fixed_timestep)
+    }
+
+    /// Sets the duration of fixed simulation steps, `None` disables fixed stepping.
+    #[allow(dead_code)] // Nothing steps a simulation yet.
+    pub fn set_fixed_timestep(&mut self, fixed_timestep: Option<Duration>) {
+        self.fixed_timestep = fixed_timestep.filter(|step| !step.is_zero());
+        self.fixed_timestep_accumulator = Duration::ZERO;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    pub fn time_scale(&self) -> f32 {
+        self.time_scale
+    }
+
+    pub fn set_time_scale(&mut self, time_scale: f32) {
+        self.time_scale = time_scale.clamp(
+            *Self::TIME_SCALE_RANGE.start(),
+            *Self::TIME_SCALE_RANGE.end(),
+        );
+    }
+
+    /// Moves the simulation clock forwards or backwards by the given amount of seconds.
+    ///
+    /// Does not affect the fixed timestep accumulator, i.e. no simulation steps are taken for the skipped time.
+    /// Non-finite amounts are ignored.
+    pub fn scrub(&mut self, seconds: f32) {
+        let Ok(offset) = Duration::try_from_secs_f32(seconds.abs()) else {
+            return;
+        };
+        self.simulation_time = if seconds < 0.0 {
+            self.simulation_time.saturating_sub(offset)
+        } else {
+            self.simulation_time.saturating_add(offset)
+        };
+    }
+}
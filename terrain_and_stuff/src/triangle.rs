@@ -0,0 +1,39 @@
+//! Placeholder scene geometry.
+
+use crate::{
+    global_bindings::GlobalBindings,
+    render_output::HdrBackbuffer,
+    resource_managers::{
+        PipelineError, PipelineManager, RenderPipelineDescriptor, RenderPipelineHandle,
+        ShaderEntryPoint,
+    },
+};
+
+/// Creates the pipeline of the placeholder scene geometry, a single triangle in the middle of the screen.
+///
+/// Drawn with three vertices and no vertex buffers into the HDR backbuffer, using the global bindings.
+pub fn create_render_pipeline(
+    device: &wgpu::Device,
+    pipeline_manager: &mut PipelineManager,
+    global_bindings: &GlobalBindings,
+) -> Result<RenderPipelineHandle, PipelineError> {
+    let pipeline_layout = pipeline_manager.pipeline_layout(
+        device,
+        "triangle",
+        &[global_bindings.bind_group_layout()],
+    );
+
+    pipeline_manager.create_render_pipeline(
+        device,
+        RenderPipelineDescriptor {
+            debug_label: "triangle".to_owned(),
+            layout: pipeline_layout,
+            vertex_shader: ShaderEntryPoint::first_in("shader.wgsl"),
+            fragment_shader: ShaderEntryPoint::first_in("shader.wgsl"),
+            fragment_targets: vec![HdrBackbuffer::FORMAT.into()],
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+        },
+    )
+}
@@ -0,0 +1,332 @@
+//! Recording of the presented frames to a video file by piping them into an `ffmpeg` process.
+//!
+//! Native only, requires `ffmpeg` to be on the `PATH`.
+//! While recording, the simulation advances by exactly one video frame per rendered frame,
+//! so videos play back smoothly regardless of how long rendering took.
+
+use std::{
+    io::Write as _,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::{mpsc, Arc},
+    time::Duration,
+};
+
+use terrain_renderer::{
+    render_output::HdrBackbuffer,
+    resource_managers::PipelineManager,
+    wgpu_utils::{DebugGroup, ReadbackBelt},
+};
+
+/// How many read back frames may queue up before rendering waits for `ffmpeg` to catch up.
+const MAX_QUEUED_FRAMES: usize = 4;
+
+/// Directory videos toggled on at runtime are saved to, relative to the working directory.
+const VIDEO_DIRECTORY: &str = "videos";
+
+#[derive(Clone)]
+pub struct VideoSettings {
+    pub fps: u32,
+
+    /// Target bitrate in `ffmpeg` notation, e.g. `20M`.
+    pub bitrate: String,
+}
+
+impl Default for VideoSettings {
+    fn default() -> Self {
+        Self {
+            fps: 60,
+            bitrate: "20M".to_owned(),
+        }
+    }
+}
+
+/// An ongoing recording.
+struct Recording {
+    path: PathBuf,
+    resolution: glam::UVec2,
+    format: wgpu::TextureFormat,
+
+    /// Copy of the presented frame, needed since surface textures can't be copied from.
+    target: wgpu::Texture,
+    target_view: wgpu::TextureView,
+
+    /// Shared with pending readbacks, which outlive the recording if it is stopped early.
+    ///
+    /// Taken out on stop to close the channel, otherwise the writer thread would wait forever.
+    frame_sender: Arc<parking_lot::Mutex<Option<mpsc::SyncSender<Vec<u8>>>>>,
+    writer_thread: std::thread::JoinHandle<()>,
+}
+
+pub struct VideoRecorder {
+    settings: VideoSettings,
+    recording: Option<Recording>,
+}
+
+impl VideoRecorder {
+    pub fn new(settings: VideoSettings) -> Self {
+        Self {
+            settings,
+            recording: None,
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    /// Resolution of the ongoing recording, if any.
+    pub fn resolution(&self) -> Option<glam::UVec2> {
+        self.recording
+            .as_ref()
+            .map(|recording| recording.resolution)
+    }
+
+    /// Time a frame of the video spans, if recording.
+    pub fn frame_duration(&self) -> Option<Duration> {
+        self.recording
+            .as_ref()
+            .map(|_| Duration::from_secs(1) / self.settings.fps.max(1))
+    }
+
+    /// Path for a new video in the video directory.
+    pub fn default_path() -> anyhow::Result<PathBuf> {
+        std::fs::create_dir_all(VIDEO_DIRECTORY)?;
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_millis();
+        Ok(Path::new(VIDEO_DIRECTORY).join(format!("recording_{timestamp}.mp4")))
+    }
+
+    /// Starts recording frames of the given resolution & surface format.
+    ///
+    /// The container & codec are picked by `ffmpeg` based on the file extension.
+    pub fn start(
+        &mut self,
+        device: &wgpu::Device,
+        path: &Path,
+        resolution: glam::UVec2,
+        format: wgpu::TextureFormat,
+    ) -> anyhow::Result<()> {
+        self.stop();
+
+        let pixel_format = match format {
+            wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => "rgba",
+            wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => "bgra",
+            _ => anyhow::bail!("Surface format {format:?} can't be recorded to video"),
+        };
+
+        let mut ffmpeg = Command::new("ffmpeg")
+            .args(["-hide_banner", "-loglevel", "error", "-y"])
+            .args(["-f", "rawvideo", "-pixel_format", pixel_format])
+            .args(["-video_size", &format!("{}x{}", resolution.x, resolution.y)])
+            .args(["-framerate", &self.settings.fps.to_string()])
+            .args(["-i", "-"])
+            // Most encoders & players need even dimensions for yuv420p.
+            .args(["-vf", "pad=ceil(iw/2)*2:ceil(ih/2)*2"])
+            .args(["-pix_fmt", "yuv420p", "-b:v", &self.settings.bitrate])
+            .arg(path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .spawn()
+            .map_err(|err| anyhow::anyhow!("Failed to start ffmpeg: {err}"))?;
+        let mut stdin = ffmpeg
+            .stdin
+            .take()
+            .expect("stdin of ffmpeg should be piped");
+
+        let (frame_sender, frame_receiver) = mpsc::sync_channel::<Vec<u8>>(MAX_QUEUED_FRAMES);
+        let writer_thread = std::thread::spawn(move || {
+            for frame in frame_receiver {
+                if let Err(err) = stdin.write_all(&frame) {
+                    log::error!("Failed to write video frame to ffmpeg: {err}");
+                    break;
+                }
+            }
+            drop(stdin);
+
+            match ffmpeg.wait() {
+                Ok(status) if status.success() => {}
+                Ok(status) => log::error!("ffmpeg exited with {status}"),
+                Err(err) => log::error!("Failed to wait for ffmpeg: {err}"),
+            }
+        });
+
+        let target = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Video frame"),
+            size: wgpu::Extent3d {
+                width: resolution.x,
+                height: resolution.y,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let target_view = target.create_view(&wgpu::TextureViewDescriptor::default());
+
+        log::info!(
+            "Recording video to {path:?} at {}x{}, {} fps",
+            resolution.x,
+            resolution.y,
+            self.settings.fps
+        );
+        self.recording = Some(Recording {
+            path: path.to_path_buf(),
+            resolution,
+            format,
+            target,
+            target_view,
+            frame_sender: Arc::new(parking_lot::Mutex::new(Some(frame_sender))),
+            writer_thread,
+        });
+
+        Ok(())
+    }
+
+    /// Stops the recording and waits for `ffmpeg` to finish the video.
+    ///
+    /// Frames whose readback didn't finish yet are lost,
+    /// so make sure to wait for all readbacks before calling this.
+    pub fn stop(&mut self) {
+        let Some(recording) = self.recording.take() else {
+            return;
+        };
+
+        recording.frame_sender.lock().take();
+        if recording.writer_thread.join().is_err() {
+            log::error!("Video writer thread panicked");
+        }
+        log::info!("Finished video {:?}", recording.path);
+    }
+
+    /// Records the HDR backbuffer's content as the next video frame, if recording.
+    ///
+    /// Videos have a fixed resolution, so the recording needs to be stopped
+    /// once the output resolution no longer matches [`VideoRecorder::resolution`].
+    pub fn record_frame(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        readback_belt: &mut ReadbackBelt,
+        hdr_backbuffer: &HdrBackbuffer,
+        pipeline_manager: &PipelineManager,
+    ) {
+        let Some(recording) = &self.recording else {
+            return;
+        };
+
+        let mut encoder = DebugGroup::new(encoder, "Video recording");
+        hdr_backbuffer.display_transform(
+            &recording.target_view,
+            None,
+            &mut encoder,
+            pipeline_manager,
+        );
+
+        let frame_sender = recording.frame_sender.clone();
+        readback_belt.read_texture(
+            device,
+            &mut encoder,
+            recording.target.as_image_copy(),
+            recording.format,
+            recording.target.size(),
+            move |readback| {
+                if let Some(frame_sender) = frame_sender.lock().as_ref() {
+                    // Fails only if the writer thread gave up already, which it logged.
+                    let _ = frame_sender.send(readback.data.to_vec());
+                }
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_available(program: &str) -> bool {
+        Command::new(program)
+            .arg("-version")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok()
+    }
+
+    /// Records a few frames rendered offscreen, skipped if there's no `ffmpeg` or no adapter.
+    #[test]
+    fn records_frames_via_ffmpeg() {
+        const NUM_FRAMES: usize = 5;
+        // Odd on purpose, to exercise the padding to even dimensions.
+        const RESOLUTION: glam::UVec2 = glam::uvec2(65, 33);
+        const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+        if !is_available("ffmpeg") {
+            eprintln!("No ffmpeg on the PATH, skipping video recording test.");
+            return;
+        }
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
+        let Some(adapter) =
+            pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default()))
+        else {
+            eprintln!("No wgpu adapter available, skipping video recording test.");
+            return;
+        };
+        let (device, queue) =
+            pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor::default(), None))
+                .unwrap();
+        let mut pipeline_manager = PipelineManager::new().unwrap();
+        let hdr_backbuffer =
+            HdrBackbuffer::new(&device, RESOLUTION, &mut pipeline_manager, FORMAT).unwrap();
+
+        let path = std::env::temp_dir().join(format!(
+            "terrain_and_stuff_video_test_{}.mp4",
+            std::process::id()
+        ));
+        let mut video_recorder = VideoRecorder::new(VideoSettings::default());
+        video_recorder
+            .start(&device, &path, RESOLUTION, FORMAT)
+            .unwrap();
+
+        let mut readback_belt = ReadbackBelt::new();
+        for _ in 0..NUM_FRAMES {
+            let mut encoder =
+                device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+            video_recorder.record_frame(
+                &device,
+                &mut encoder,
+                &mut readback_belt,
+                &hdr_backbuffer,
+                &pipeline_manager,
+            );
+            queue.submit(Some(encoder.finish()));
+            readback_belt.after_queue_submit();
+        }
+        while readback_belt.num_pending() > 0 {
+            device.poll(wgpu::Maintain::Wait);
+            readback_belt.poll(&device);
+        }
+        video_recorder.stop();
+
+        let video_size = std::fs::metadata(&path).map_or(0, |metadata| metadata.len());
+        let num_frames = is_available("ffprobe").then(|| {
+            let output = Command::new("ffprobe")
+                .args(["-v", "error", "-count_frames", "-select_streams", "v:0"])
+                .args(["-show_entries", "stream=nb_read_frames", "-of", "csv=p=0"])
+                .arg(&path)
+                .output()
+                .unwrap();
+            String::from_utf8_lossy(&output.stdout).trim().to_owned()
+        });
+        std::fs::remove_file(&path).ok();
+
+        assert!(video_size > 0, "ffmpeg didn't write a video");
+        if let Some(num_frames) = num_frames {
+            assert_eq!(num_frames, NUM_FRAMES.to_string());
+        }
+    }
+}
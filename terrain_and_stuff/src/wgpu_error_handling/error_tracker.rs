@@ -19,6 +19,20 @@ pub struct ErrorEntry {
     description: String,
 }
 
+/// Record of an error that occurred at some point during the application's lifetime.
+pub struct ErrorHistoryEntry {
+    pub description: String,
+
+    /// Frame index for the frame on which this error occurred first.
+    pub first_occurred_frame_index: u64,
+
+    /// Frame index for the frame on which this error occurred most recently.
+    pub last_occurred_frame_index: u64,
+
+    /// How often the error occurred in total, counting at most once per frame.
+    pub occurrence_count: u64,
+}
+
 /// Keeps track of wgpu errors and de-duplicates messages across frames.
 ///
 /// On native & webgl, what accounts for as an error duplicate is a heuristic based on wgpu-core error type.
@@ -27,7 +41,11 @@ pub struct ErrorEntry {
 /// The application maintains a "top level" error tracker for all otherwise unhandled errors.
 #[derive(Default)]
 pub struct ErrorTracker {
+    /// Recently occurred errors by type, used to de-duplicate them.
     pub errors: Mutex<HashMap<ContextError, ErrorEntry>>,
+
+    /// All errors that ever occurred, unlike `errors` this is never cleaned up.
+    history: Mutex<Vec<ErrorHistoryEntry>>,
 }
 
 impl ErrorTracker {
@@ -44,6 +62,58 @@ impl ErrorTracker {
         });
     }
 
+    /// Total number of error occurrences, counting each error at most once per frame.
+    pub fn total_error_count(&self) -> u64 {
+        self.history
+            .lock()
+            .iter()
+            .map(|entry| entry.occurrence_count)
+            .sum()
+    }
+
+    /// Logs a summary of all errors that occurred so far.
+    pub fn log_error_history(&self) {
+        let history = self.history.lock();
+        if history.is_empty() {
+            log::info!("No wgpu errors occurred so far.");
+            return;
+        }
+
+        log::info!("{} distinct wgpu errors occurred so far:", history.len());
+        for entry in history.iter() {
+            log::info!(
+                "  {}x, frames {}..={}: {}",
+                entry.occurrence_count,
+                entry.first_occurred_frame_index,
+                entry.last_occurred_frame_index,
+                entry.description
+            );
+        }
+    }
+
+    fn record_in_history(&self, description: &str, frame_index: u64) {
+        /// Limit history size in case something produces an endless stream of distinct errors.
+        const MAX_HISTORY_ENTRIES: usize = 256;
+
+        let mut history = self.history.lock();
+        if let Some(entry) = history
+            .iter_mut()
+            .find(|entry| entry.description == description)
+        {
+            if entry.last_occurred_frame_index != frame_index {
+                entry.occurrence_count += 1;
+                entry.last_occurred_frame_index = frame_index;
+            }
+        } else if history.len() < MAX_HISTORY_ENTRIES {
+            history.push(ErrorHistoryEntry {
+                description: description.to_owned(),
+                first_occurred_frame_index: frame_index,
+                last_occurred_frame_index: frame_index,
+                occurrence_count: 1,
+            });
+        }
+    }
+
     /// Handles an async error, calling [`ErrorTracker::handle_error`] as needed.
     ///
     /// `on_last_scope_resolved` is called when the last scope has resolved.
@@ -102,6 +172,7 @@ impl ErrorTracker {
         match error {
             wgpu::Error::OutOfMemory { source: _ } => {
                 log::error!("A wgpu operation caused out-of-memory: {error}");
+                self.record_in_history(&error.to_string(), frame_index);
             }
             wgpu::Error::Internal {
                 source: _source,
@@ -136,6 +207,8 @@ impl ErrorTracker {
                 #[cfg(target_arch = "wasm32")]
                 let ctx_err = description.clone();
 
+                self.record_in_history(&description, frame_index);
+
                 if self.errors.lock().insert(ctx_err, entry).is_none() {
                     let base_description = if is_internal_error {
                         "Internal wgpu error"
@@ -1,3 +1,5 @@
+//! Capturing wgpu errors per frame via error scopes & keeping a history of them, so repeated errors aren't spammed.
+
 mod error_tracker;
 mod now_or_never;
 mod wgpu_error_scope;
@@ -11,6 +11,7 @@ pub struct WgpuErrorScope {
 }
 
 impl WgpuErrorScope {
+    /// Pushes the error scopes onto the device.
     pub fn start(device: &Arc<wgpu::Device>) -> Self {
         device.push_error_scope(wgpu::ErrorFilter::Validation);
         device.push_error_scope(wgpu::ErrorFilter::OutOfMemory);
@@ -22,6 +23,7 @@ impl WgpuErrorScope {
         }
     }
 
+    /// Pops the error scopes, the returned futures resolve once their errors are known.
     pub fn end(
         mut self,
     ) -> [impl std::future::Future<Output = Option<wgpu::Error>> + Send + 'static; 2] {
@@ -1,15 +1,25 @@
+/// Bind group layout along with the entries it was created from, so bind groups can follow it.
 pub struct BindGroupLayoutWithDesc {
+    /// The layout itself.
     pub layout: wgpu::BindGroupLayout,
+    /// Entries of the layout in binding order.
     pub entries: Vec<wgpu::BindGroupLayoutEntry>,
 }
 
+/// Builder for bind group layouts that assigns consecutive binding indices.
 pub struct BindGroupLayoutBuilder {
     entries: Vec<wgpu::BindGroupLayoutEntry>,
     next_binding_index: u32,
 }
 
-#[allow(dead_code)]
+impl Default for BindGroupLayoutBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl BindGroupLayoutBuilder {
+    /// Starts an empty layout, the first binding gets index 0.
     pub fn new() -> Self {
         BindGroupLayoutBuilder {
             entries: Vec::new(),
@@ -17,12 +27,14 @@ impl BindGroupLayoutBuilder {
         }
     }
 
+    /// Adds an entry with an explicit binding index, following bindings continue after it.
     pub fn binding(mut self, binding: wgpu::BindGroupLayoutEntry) -> Self {
         self.next_binding_index = binding.binding + 1;
         self.entries.push(binding);
         self
     }
 
+    /// Adds a binding at the next index.
     pub fn next_binding(self, visibility: wgpu::ShaderStages, ty: wgpu::BindingType) -> Self {
         let binding = self.next_binding_index;
         self.binding(wgpu::BindGroupLayoutEntry {
@@ -33,14 +45,17 @@ impl BindGroupLayoutBuilder {
         })
     }
 
+    /// Adds a binding at the next index that is visible to compute shaders.
     pub fn next_binding_compute(self, ty: wgpu::BindingType) -> Self {
         self.next_binding(wgpu::ShaderStages::COMPUTE, ty)
     }
 
+    /// Adds a binding at the next index that is visible to fragment shaders.
     pub fn next_binding_fragment(self, ty: wgpu::BindingType) -> Self {
         self.next_binding(wgpu::ShaderStages::FRAGMENT, ty)
     }
 
+    /// Adds a binding at the next index that is visible to vertex shaders.
     pub fn next_binding_vertex(self, ty: wgpu::BindingType) -> Self {
         self.next_binding(wgpu::ShaderStages::VERTEX, ty)
     }
@@ -49,6 +64,7 @@ impl BindGroupLayoutBuilder {
     //    self.next_binding(wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT, ty)
     //}
 
+    /// Adds a binding at the next index that is visible to all shader stages.
     pub fn next_binding_all(self, ty: wgpu::BindingType) -> Self {
         self.next_binding(
             wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT | wgpu::ShaderStages::COMPUTE,
@@ -56,6 +72,7 @@ impl BindGroupLayoutBuilder {
         )
     }
 
+    /// Creates the layout.
     pub fn create(self, device: &wgpu::Device, label: &str) -> BindGroupLayoutWithDesc {
         BindGroupLayoutWithDesc {
             layout: device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
@@ -67,15 +84,16 @@ impl BindGroupLayoutBuilder {
     }
 }
 
-// Builder for wgpu::BindGroups following the exact layout from a wgpu::BindGroupLayout
-// Makes life simpler by assuming that order of elements in the bind group is equal to order of elements in the bind group layout.
+/// Builder for wgpu::BindGroups following the exact layout from a wgpu::BindGroupLayout
+///
+/// Makes life simpler by assuming that order of elements in the bind group is equal to order of elements in the bind group layout.
 pub struct BindGroupBuilder<'a> {
     layout_with_desc: &'a BindGroupLayoutWithDesc,
     entries: Vec<wgpu::BindGroupEntry<'a>>,
 }
 
-#[allow(dead_code)]
 impl<'a> BindGroupBuilder<'a> {
+    /// Starts a bind group for the given layout, resources need to be added in the layout's order.
     pub fn new(layout_with_desc: &'a BindGroupLayoutWithDesc) -> Self {
         BindGroupBuilder {
             layout_with_desc,
@@ -83,7 +101,7 @@ impl<'a> BindGroupBuilder<'a> {
         }
     }
 
-    // Uses same binding index as binding group layout at the same ordering
+    /// Uses same binding index as binding group layout at the same ordering
     pub fn resource(mut self, resource: wgpu::BindingResource<'a>) -> Self {
         assert!(self.entries.len() < self.layout_with_desc.entries.len());
 
@@ -94,18 +112,22 @@ impl<'a> BindGroupBuilder<'a> {
         self
     }
 
+    /// Adds a buffer binding as the next resource.
     pub fn buffer(self, buffer_binding: wgpu::BufferBinding<'a>) -> Self {
         self.resource(wgpu::BindingResource::Buffer(buffer_binding))
     }
 
+    /// Adds a sampler as the next resource.
     pub fn sampler(self, sampler: &'a wgpu::Sampler) -> Self {
         self.resource(wgpu::BindingResource::Sampler(sampler))
     }
 
+    /// Adds a texture view as the next resource.
     pub fn texture(self, texture_view: &'a wgpu::TextureView) -> Self {
         self.resource(wgpu::BindingResource::TextureView(texture_view))
     }
 
+    /// Creates the bind group, panics if not all entries of the layout got a resource.
     pub fn create(&self, device: &wgpu::Device, label: &str) -> wgpu::BindGroup {
         assert_eq!(self.entries.len(), self.layout_with_desc.entries.len());
 
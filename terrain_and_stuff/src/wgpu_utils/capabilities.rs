@@ -0,0 +1,94 @@
+/// Optional GPU features & limits, detected once at device creation.
+///
+/// None of these are required to run. Subsystems query this instead of the device
+/// and pick a fallback if a capability is missing.
+#[derive(Clone, Copy, Debug)]
+pub struct GpuCapabilities {
+    /// Block compressed textures are uploaded directly, otherwise they are decoded on the CPU.
+    pub texture_compression_bc: bool,
+
+    /// Line polygon mode, otherwise wireframe rendering is disabled.
+    pub polygon_mode_line: bool,
+
+    /// Dual-source blending, i.e. a second blend source from the fragment shader.
+    pub dual_source_blending: bool,
+
+    /// Timestamp queries, both at pass boundaries and inside of encoders.
+    pub timestamp_queries: bool,
+
+    /// Pipeline statistics queries, i.e. shader invocation & primitive counts per pass. Never available on WebGPU.
+    pub pipeline_statistics_queries: bool,
+
+    /// Upper bound for the product of a compute shader's workgroup size.
+    pub max_compute_invocations_per_workgroup: u32,
+}
+
+impl GpuCapabilities {
+    /// All features that are enabled if the adapter supports them.
+    const OPTIONAL_FEATURES: wgpu::Features = wgpu::Features::TEXTURE_COMPRESSION_BC
+        .union(wgpu::Features::POLYGON_MODE_LINE)
+        .union(wgpu::Features::DUAL_SOURCE_BLENDING)
+        .union(wgpu::Features::TIMESTAMP_QUERY)
+        .union(wgpu::Features::TIMESTAMP_QUERY_INSIDE_ENCODERS)
+        .union(wgpu::Features::PIPELINE_STATISTICS_QUERY);
+
+    /// Features to request on device creation.
+    pub fn features_to_request(adapter: &wgpu::Adapter) -> wgpu::Features {
+        adapter.features() & Self::OPTIONAL_FEATURES
+    }
+
+    /// Reads the capabilities off a device created with [`GpuCapabilities::features_to_request`].
+    pub fn from_device(device: &wgpu::Device) -> Self {
+        let features = device.features();
+        let limits = device.limits();
+
+        Self {
+            texture_compression_bc: features.contains(wgpu::Features::TEXTURE_COMPRESSION_BC),
+            polygon_mode_line: features.contains(wgpu::Features::POLYGON_MODE_LINE),
+            dual_source_blending: features.contains(wgpu::Features::DUAL_SOURCE_BLENDING),
+            timestamp_queries: features.contains(
+                wgpu::Features::TIMESTAMP_QUERY | wgpu::Features::TIMESTAMP_QUERY_INSIDE_ENCODERS,
+            ),
+            pipeline_statistics_queries: features
+                .contains(wgpu::Features::PIPELINE_STATISTICS_QUERY),
+            max_compute_invocations_per_workgroup: limits.max_compute_invocations_per_workgroup,
+        }
+    }
+}
+
+impl std::fmt::Display for GpuCapabilities {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let supported = |supported: bool| if supported { "yes" } else { "no" };
+
+        writeln!(
+            f,
+            "  BC texture compression:  {}",
+            supported(self.texture_compression_bc)
+        )?;
+        writeln!(
+            f,
+            "  Line polygon mode:       {}",
+            supported(self.polygon_mode_line)
+        )?;
+        writeln!(
+            f,
+            "  Dual-source blending:    {}",
+            supported(self.dual_source_blending)
+        )?;
+        writeln!(
+            f,
+            "  Timestamp queries:       {}",
+            supported(self.timestamp_queries)
+        )?;
+        writeln!(
+            f,
+            "  Pipeline statistics:     {}",
+            supported(self.pipeline_statistics_queries)
+        )?;
+        write!(
+            f,
+            "  Max compute invocations: {}",
+            self.max_compute_invocations_per_workgroup
+        )
+    }
+}
@@ -0,0 +1,93 @@
+/// Debug group on a command encoder that is automatically popped when dropped.
+///
+/// Derefs to the encoder, so all commands recorded through it end up in the group.
+/// Debug groups show up as nested regions in graphics debuggers like RenderDoc & Xcode
+/// as well as in validation layer messages.
+pub struct DebugGroup<'a> {
+    encoder: &'a mut wgpu::CommandEncoder,
+}
+
+impl<'a> DebugGroup<'a> {
+    /// Pushes a debug group with the given label.
+    pub fn new(encoder: &'a mut wgpu::CommandEncoder, label: &str) -> Self {
+        encoder.push_debug_group(label);
+        Self { encoder }
+    }
+}
+
+impl std::ops::Deref for DebugGroup<'_> {
+    type Target = wgpu::CommandEncoder;
+
+    fn deref(&self) -> &Self::Target {
+        self.encoder
+    }
+}
+
+impl std::ops::DerefMut for DebugGroup<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.encoder
+    }
+}
+
+impl Drop for DebugGroup<'_> {
+    fn drop(&mut self) {
+        self.encoder.pop_debug_group();
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum CaptureState {
+    Idle,
+    Requested,
+    Capturing,
+}
+
+/// Triggers a graphics debugger capture (RenderDoc, Xcode, ...) for exactly one frame.
+///
+/// Does nothing if no graphics debugger is attached or the backend doesn't support programmatic captures.
+pub struct FrameCapture {
+    state: CaptureState,
+}
+
+impl Default for FrameCapture {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FrameCapture {
+    /// No capture is requested initially.
+    pub fn new() -> Self {
+        Self {
+            state: CaptureState::Idle,
+        }
+    }
+
+    /// Captures the next frame that is started via [`FrameCapture::begin_frame`].
+    pub fn request_capture(&mut self) {
+        if self.state == CaptureState::Idle {
+            self.state = CaptureState::Requested;
+        }
+    }
+
+    /// Starts a capture if one was requested.
+    ///
+    /// Call before recording any GPU work of the frame.
+    pub fn begin_frame(&mut self, device: &wgpu::Device, frame_index: u64) {
+        if self.state == CaptureState::Requested {
+            log::info!("Starting graphics debugger capture of frame {frame_index}");
+            device.start_capture();
+            self.state = CaptureState::Capturing;
+        }
+    }
+
+    /// Ends a capture if one is in progress.
+    ///
+    /// Call after the frame was submitted & presented.
+    pub fn end_frame(&mut self, device: &wgpu::Device) {
+        if self.state == CaptureState::Capturing {
+            device.stop_capture();
+            self.state = CaptureState::Idle;
+        }
+    }
+}
@@ -0,0 +1,248 @@
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use web_time::{Duration, Instant};
+
+use super::{
+    submission_timer::{add_sample, format_report, ScopeStatistics},
+    GpuCapabilities, ReadbackBelt,
+};
+
+const TIMESTAMP_SIZE: wgpu::BufferAddress = std::mem::size_of::<u64>() as _;
+
+/// Results of completed frames, written by readback callbacks.
+#[derive(Default)]
+struct Measurements {
+    statistics: Vec<ScopeStatistics>,
+    frame_times: Vec<Duration>,
+    scope_times: Vec<(&'static str, Duration)>,
+}
+
+/// Precise per-scope GPU timings from timestamp queries written into command encoders.
+///
+/// Unlike the fence based [`super::SubmissionTimer`], scopes don't need to be submitted separately
+/// and the timings don't depend on when the device is polled.
+/// Timestamps are resolved at the end of each frame and read back via the readback belt.
+///
+/// Requires timestamp queries inside of encoders, which WebGPU doesn't offer; without them this does nothing.
+pub struct GpuTimer {
+    /// `None` if timestamp queries aren't supported.
+    query_set: Option<wgpu::QuerySet>,
+    resolve_buffer: Option<wgpu::Buffer>,
+    /// Nanoseconds per timestamp tick.
+    timestamp_period: f32,
+
+    log_reports: bool,
+    record_frame_times: bool,
+    record_scope_times: bool,
+
+    /// Scopes of the current frame, in query index order. Each scope has a begin & end timestamp.
+    frame_scopes: Vec<&'static str>,
+    scope_open: bool,
+    measurements: Arc<Mutex<Measurements>>,
+    last_report: Instant,
+}
+
+impl GpuTimer {
+    const REPORT_INTERVAL: Duration = Duration::from_secs(1);
+
+    /// Scopes beyond this many per frame are not measured.
+    const MAX_SCOPES_PER_FRAME: u32 = 8;
+
+    /// Frame times that weren't taken are dropped after this many frames.
+    const MAX_FRAME_TIMES: usize = 64;
+
+    /// Scope times that weren't taken are dropped after this many scopes.
+    const MAX_SCOPE_TIMES: usize = Self::MAX_FRAME_TIMES * Self::MAX_SCOPES_PER_FRAME as usize;
+
+    /// Does nothing unless timestamp queries are supported.
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue, capabilities: &GpuCapabilities) -> Self {
+        let (query_set, resolve_buffer) = if capabilities.timestamp_queries {
+            let num_queries = Self::MAX_SCOPES_PER_FRAME * 2;
+            let query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
+                label: Some("GPU timer"),
+                ty: wgpu::QueryType::Timestamp,
+                count: num_queries,
+            });
+            let resolve_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("GPU timer resolve"),
+                size: TIMESTAMP_SIZE * num_queries as wgpu::BufferAddress,
+                usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+                mapped_at_creation: false,
+            });
+            (Some(query_set), Some(resolve_buffer))
+        } else {
+            (None, None)
+        };
+
+        Self {
+            query_set,
+            resolve_buffer,
+            timestamp_period: queue.get_timestamp_period(),
+            log_reports: false,
+            record_frame_times: false,
+            record_scope_times: false,
+            frame_scopes: Vec::new(),
+            scope_open: false,
+            measurements: Arc::new(Mutex::new(Measurements::default())),
+            last_report: Instant::now(),
+        }
+    }
+
+    /// Whether timestamp queries are available, otherwise nothing is measured.
+    pub fn is_supported(&self) -> bool {
+        self.query_set.is_some()
+    }
+
+    fn is_active(&self) -> bool {
+        self.is_supported()
+            && (self.log_reports || self.record_frame_times || self.record_scope_times)
+    }
+
+    /// Enables logging of per-scope averages once per second, see [`GpuTimer::report`].
+    pub fn set_log_reports(&mut self, log_reports: bool) {
+        self.log_reports = log_reports;
+        self.measurements.lock().statistics.clear();
+        self.last_report = Instant::now();
+    }
+
+    /// Enables recording of total GPU frame times, see [`GpuTimer::take_frame_times`].
+    pub fn set_record_frame_times(&mut self, record_frame_times: bool) {
+        self.record_frame_times = record_frame_times;
+        self.measurements.lock().frame_times.clear();
+    }
+
+    /// Total GPU times of all frames read back since the last call, oldest first.
+    ///
+    /// A frame's time is the sum of its scopes.
+    pub fn take_frame_times(&mut self) -> Vec<Duration> {
+        std::mem::take(&mut self.measurements.lock().frame_times)
+    }
+
+    /// Enables recording of the GPU time of every scope, see [`GpuTimer::take_scope_times`].
+    pub fn set_record_scope_times(&mut self, record_scope_times: bool) {
+        self.record_scope_times = record_scope_times;
+        self.measurements.lock().scope_times.clear();
+    }
+
+    /// Label & GPU time of every scope read back since the last call, oldest first.
+    pub fn take_scope_times(&mut self) -> Vec<(&'static str, Duration)> {
+        std::mem::take(&mut self.measurements.lock().scope_times)
+    }
+
+    /// Starts a timed scope, must be followed by [`GpuTimer::end_scope`] before the next scope starts.
+    ///
+    /// Must not be called while a pass is recorded on the encoder.
+    pub fn begin_scope(&mut self, encoder: &mut wgpu::CommandEncoder, label: &'static str) {
+        let Some(query_set) = &self.query_set else {
+            return;
+        };
+        if !self.is_active() || self.frame_scopes.len() >= Self::MAX_SCOPES_PER_FRAME as usize {
+            return;
+        }
+
+        encoder.write_timestamp(query_set, self.frame_scopes.len() as u32 * 2);
+        self.frame_scopes.push(label);
+        self.scope_open = true;
+    }
+
+    /// Ends the scope started by [`GpuTimer::begin_scope`].
+    ///
+    /// Does nothing if `begin_scope` didn't start a scope.
+    pub fn end_scope(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        let Some(query_set) = &self.query_set else {
+            return;
+        };
+        if std::mem::take(&mut self.scope_open) {
+            encoder.write_timestamp(query_set, self.frame_scopes.len() as u32 * 2 - 1);
+        }
+    }
+
+    /// Resolves all timestamps of the frame & schedules their readback.
+    ///
+    /// Must be recorded after all scopes of the frame, i.e. into the last encoder submitted.
+    pub fn end_frame(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        readback_belt: &mut ReadbackBelt,
+    ) {
+        let (Some(query_set), Some(resolve_buffer)) = (&self.query_set, &self.resolve_buffer)
+        else {
+            return;
+        };
+        if self.frame_scopes.is_empty() {
+            return;
+        }
+
+        let num_queries = self.frame_scopes.len() as u32 * 2;
+        encoder.resolve_query_set(query_set, 0..num_queries, resolve_buffer, 0);
+
+        let frame_scopes = std::mem::take(&mut self.frame_scopes);
+        let timestamp_period = self.timestamp_period as f64;
+        let log_reports = self.log_reports;
+        let record_frame_times = self.record_frame_times;
+        let record_scope_times = self.record_scope_times;
+        let measurements = self.measurements.clone();
+        readback_belt.read_buffer(
+            device,
+            encoder,
+            resolve_buffer,
+            0,
+            TIMESTAMP_SIZE * num_queries as wgpu::BufferAddress,
+            move |readback| {
+                let timestamps: Vec<u64> = readback
+                    .data
+                    .chunks_exact(TIMESTAMP_SIZE as usize)
+                    .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+                    .collect();
+
+                let mut measurements = measurements.lock();
+                let mut frame_time = Duration::ZERO;
+                for (label, timestamps) in frame_scopes.into_iter().zip(timestamps.chunks_exact(2))
+                {
+                    // Timestamps may not be monotonic on all GPUs, e.g. across power state changes.
+                    let ticks = timestamps[1].saturating_sub(timestamps[0]);
+                    let duration = Duration::from_nanos((ticks as f64 * timestamp_period) as u64);
+                    frame_time += duration;
+
+                    if log_reports {
+                        add_sample(&mut measurements.statistics, label, duration);
+                    }
+                    if record_scope_times {
+                        if measurements.scope_times.len() >= Self::MAX_SCOPE_TIMES {
+                            measurements.scope_times.remove(0);
+                        }
+                        measurements.scope_times.push((label, duration));
+                    }
+                }
+
+                if record_frame_times {
+                    if measurements.frame_times.len() >= Self::MAX_FRAME_TIMES {
+                        measurements.frame_times.remove(0);
+                    }
+                    measurements.frame_times.push(frame_time);
+                }
+            },
+        );
+    }
+
+    /// Logs a report of all completed frames if it is due.
+    pub fn report(&mut self) {
+        if !self.log_reports || self.last_report.elapsed() < Self::REPORT_INTERVAL {
+            return;
+        }
+
+        let mut measurements = self.measurements.lock();
+        if measurements.statistics.is_empty() {
+            return;
+        }
+        log::info!(
+            "GPU timings (timestamp queries):\n{}",
+            format_report(&measurements.statistics)
+        );
+
+        measurements.statistics.clear();
+        self.last_report = Instant::now();
+    }
+}
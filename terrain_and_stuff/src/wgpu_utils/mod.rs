@@ -1,7 +1,21 @@
+//! Helpers on top of wgpu that aren't specific to any pass: bind groups, readback, GPU timing & debug markers.
+
 mod binding_builder;
+mod capabilities;
+mod debug_markers;
+mod gpu_timer;
+mod pipeline_statistics;
+mod readback;
+mod submission_timer;
 //mod uniformbuffer;
 
 pub use binding_builder::{BindGroupBuilder, BindGroupLayoutBuilder, BindGroupLayoutWithDesc};
+pub use capabilities::GpuCapabilities;
+pub use debug_markers::{DebugGroup, FrameCapture};
+pub use gpu_timer::GpuTimer;
+pub use pipeline_statistics::PipelineStatistics;
+pub use readback::ReadbackBelt;
+pub use submission_timer::SubmissionTimer;
 //pub use uniformbuffer::UniformBuffer;
 
 // pub fn compute_group_size(
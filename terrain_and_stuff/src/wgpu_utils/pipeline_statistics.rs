@@ -0,0 +1,223 @@
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use web_time::{Duration, Instant};
+
+use super::{GpuCapabilities, ReadbackBelt};
+
+/// Counts gathered by a single pipeline statistics query, in query set order.
+const STATISTICS_TYPES: wgpu::PipelineStatisticsTypes =
+    wgpu::PipelineStatisticsTypes::VERTEX_SHADER_INVOCATIONS
+        .union(wgpu::PipelineStatisticsTypes::CLIPPER_PRIMITIVES_OUT)
+        .union(wgpu::PipelineStatisticsTypes::FRAGMENT_SHADER_INVOCATIONS);
+
+const NUM_VALUES_PER_QUERY: usize = 3;
+const QUERY_SIZE: wgpu::BufferAddress = (NUM_VALUES_PER_QUERY * std::mem::size_of::<u64>()) as _;
+
+/// Accumulated counts of a pass since the last report.
+#[derive(Clone, Copy, Default)]
+struct PassStatistics {
+    vertex_shader_invocations: u64,
+    primitives: u64,
+    fragment_shader_invocations: u64,
+
+    /// Pixels of the pass' color target, used to determine overdraw.
+    target_pixels: u64,
+    num_samples: u64,
+}
+
+/// Per-pass vertex, primitive & fragment shader invocation counts from pipeline statistics queries.
+///
+/// Meant to be looked at next to the submission timings:
+/// fragment invocations per target pixel quantify overdraw, primitive counts the effect of culling & LOD.
+/// Queries are resolved at the end of each frame and read back via the readback belt,
+/// reports are logged once per second.
+///
+/// Pipeline statistics queries are a native-only feature, on WebGPU this does nothing.
+pub struct PipelineStatistics {
+    /// `None` if pipeline statistics queries aren't supported.
+    query_set: Option<wgpu::QuerySet>,
+    resolve_buffer: Option<wgpu::Buffer>,
+
+    enabled: bool,
+
+    /// Passes of the current frame, in query index order.
+    frame_passes: Vec<(&'static str, u64)>,
+    query_active: bool,
+    statistics: Arc<Mutex<Vec<(&'static str, PassStatistics)>>>,
+    last_report: Instant,
+}
+
+impl PipelineStatistics {
+    const REPORT_INTERVAL: Duration = Duration::from_secs(1);
+
+    /// Passes beyond this many per frame are not measured.
+    const MAX_PASSES_PER_FRAME: u32 = 8;
+
+    /// Starts out disabled, does nothing unless pipeline statistics queries are supported.
+    pub fn new(device: &wgpu::Device, capabilities: &GpuCapabilities) -> Self {
+        let (query_set, resolve_buffer) = if capabilities.pipeline_statistics_queries {
+            let query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
+                label: Some("Pipeline statistics"),
+                ty: wgpu::QueryType::PipelineStatistics(STATISTICS_TYPES),
+                count: Self::MAX_PASSES_PER_FRAME,
+            });
+            let resolve_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Pipeline statistics resolve"),
+                size: QUERY_SIZE * Self::MAX_PASSES_PER_FRAME as wgpu::BufferAddress,
+                usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+                mapped_at_creation: false,
+            });
+            (Some(query_set), Some(resolve_buffer))
+        } else {
+            (None, None)
+        };
+
+        Self {
+            query_set,
+            resolve_buffer,
+            enabled: false,
+            frame_passes: Vec::new(),
+            query_active: false,
+            statistics: Arc::new(Mutex::new(Vec::new())),
+            last_report: Instant::now(),
+        }
+    }
+
+    /// Whether pipeline statistics queries are available, otherwise nothing is measured.
+    pub fn is_supported(&self) -> bool {
+        self.query_set.is_some()
+    }
+
+    /// Whether passes are measured & reported.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Enables measuring, which stays off if queries aren't supported. Discards all pending statistics.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled && self.is_supported();
+        self.frame_passes.clear();
+        self.statistics.lock().clear();
+        self.last_report = Instant::now();
+    }
+
+    /// Starts measuring a render pass, must be followed by [`PipelineStatistics::end_pass`] on the same pass.
+    ///
+    /// `num_target_pixels` is the pixel count of the pass' color target.
+    pub fn begin_pass(
+        &mut self,
+        pass: &mut wgpu::RenderPass<'_>,
+        label: &'static str,
+        num_target_pixels: u64,
+    ) {
+        let Some(query_set) = &self.query_set else {
+            return;
+        };
+        if !self.enabled || self.frame_passes.len() >= Self::MAX_PASSES_PER_FRAME as usize {
+            return;
+        }
+
+        pass.begin_pipeline_statistics_query(query_set, self.frame_passes.len() as u32);
+        self.frame_passes.push((label, num_target_pixels));
+        self.query_active = true;
+    }
+
+    /// Ends the query started by [`PipelineStatistics::begin_pass`].
+    ///
+    /// Does nothing if `begin_pass` didn't start a query.
+    pub fn end_pass(&mut self, pass: &mut wgpu::RenderPass<'_>) {
+        if std::mem::take(&mut self.query_active) {
+            pass.end_pipeline_statistics_query();
+        }
+    }
+
+    /// Resolves all queries of the frame & schedules their readback.
+    ///
+    /// Must be recorded after all passes of the frame, i.e. into the last encoder submitted.
+    pub fn end_frame(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        readback_belt: &mut ReadbackBelt,
+    ) {
+        let (Some(query_set), Some(resolve_buffer)) = (&self.query_set, &self.resolve_buffer)
+        else {
+            return;
+        };
+        if self.frame_passes.is_empty() {
+            return;
+        }
+
+        let num_queries = self.frame_passes.len() as u32;
+        encoder.resolve_query_set(query_set, 0..num_queries, resolve_buffer, 0);
+
+        let frame_passes = std::mem::take(&mut self.frame_passes);
+        let statistics = self.statistics.clone();
+        readback_belt.read_buffer(
+            device,
+            encoder,
+            resolve_buffer,
+            0,
+            QUERY_SIZE * num_queries as wgpu::BufferAddress,
+            move |readback| {
+                let values: Vec<u64> = readback
+                    .data
+                    .chunks_exact(std::mem::size_of::<u64>())
+                    .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+                    .collect();
+
+                let mut statistics = statistics.lock();
+                for ((label, target_pixels), values) in frame_passes
+                    .into_iter()
+                    .zip(values.chunks_exact(NUM_VALUES_PER_QUERY))
+                {
+                    let index = statistics
+                        .iter()
+                        .position(|(existing_label, _)| *existing_label == label)
+                        .unwrap_or_else(|| {
+                            statistics.push((label, PassStatistics::default()));
+                            statistics.len() - 1
+                        });
+                    let pass = &mut statistics[index].1;
+                    pass.vertex_shader_invocations += values[0];
+                    pass.primitives += values[1];
+                    pass.fragment_shader_invocations += values[2];
+                    pass.target_pixels += target_pixels;
+                    pass.num_samples += 1;
+                }
+            },
+        );
+    }
+
+    /// Logs a report of all completed frames if it is due.
+    pub fn report(&mut self) {
+        if !self.enabled || self.last_report.elapsed() < Self::REPORT_INTERVAL {
+            return;
+        }
+
+        let mut statistics = self.statistics.lock();
+        if statistics.is_empty() {
+            return;
+        }
+
+        let report = statistics
+            .iter()
+            .map(|(label, pass)| {
+                let average = |value: u64| value / pass.num_samples;
+                format!(
+                    "  {label}: {} vertices, {} primitives, {} fragments ({:.2} per pixel)",
+                    average(pass.vertex_shader_invocations),
+                    average(pass.primitives),
+                    average(pass.fragment_shader_invocations),
+                    pass.fragment_shader_invocations as f64 / pass.target_pixels.max(1) as f64
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        log::info!("Pipeline statistics (average per frame):\n{report}");
+
+        statistics.clear();
+        self.last_report = Instant::now();
+    }
+}
@@ -0,0 +1,323 @@
+use std::sync::{
+    atomic::{AtomicU8, Ordering},
+    Arc,
+};
+
+/// Data handed out to a readback callback once the GPU finished the copy.
+pub struct ReadbackData<'a> {
+    /// Frame index on which the readback was scheduled.
+    ///
+    /// Compare with the current frame index to find out how many frames of latency there were.
+    pub frame_index: u64,
+
+    /// Tightly packed data, i.e. any row padding needed for texture copies has already been removed.
+    pub data: &'a [u8],
+}
+
+type ReadbackCallback = Box<dyn FnOnce(ReadbackData<'_>)>;
+
+/// How the data in the staging buffer is laid out.
+enum ReadbackLayout {
+    /// Plain buffer copy.
+    Linear { size: u64 },
+
+    /// Texture copy with padded rows.
+    Rows {
+        unpadded_bytes_per_row: u32,
+        padded_bytes_per_row: u32,
+        num_rows: u32,
+    },
+}
+
+impl ReadbackLayout {
+    fn staging_size(&self) -> u64 {
+        match self {
+            Self::Linear { size } => *size,
+            Self::Rows {
+                padded_bytes_per_row,
+                num_rows,
+                ..
+            } => *padded_bytes_per_row as u64 * *num_rows as u64,
+        }
+    }
+}
+
+const MAP_STATE_PENDING: u8 = 0;
+const MAP_STATE_MAPPED: u8 = 1;
+const MAP_STATE_FAILED: u8 = 2;
+
+struct PendingReadback {
+    buffer: wgpu::Buffer,
+    layout: ReadbackLayout,
+    frame_index: u64,
+    callback: ReadbackCallback,
+
+    /// Set once `map_async` was called, which may only happen after the copy was submitted.
+    map_state: Option<Arc<AtomicU8>>,
+}
+
+/// Manages a pool of staging buffers for reading back GPU data to the CPU.
+///
+/// Usage per frame:
+/// * [`ReadbackBelt::begin_frame`] with the current frame index
+/// * record any number of [`ReadbackBelt::read_buffer`] / [`ReadbackBelt::read_texture`] copies into the frame's encoder
+/// * [`ReadbackBelt::after_queue_submit`] once the encoder got submitted
+/// * [`ReadbackBelt::poll`] to invoke callbacks of all readbacks that finished in the meantime
+///
+/// Staging buffers are recycled once their data has been handed out.
+pub struct ReadbackBelt {
+    frame_index: u64,
+    free_buffers: Vec<wgpu::Buffer>,
+    pending: Vec<PendingReadback>,
+}
+
+impl Default for ReadbackBelt {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReadbackBelt {
+    /// How many unused staging buffers are kept around at most.
+    const MAX_FREE_BUFFERS: usize = 8;
+
+    /// After how many frames an outstanding readback is considered suspicious.
+    const LATENCY_WARNING_THRESHOLD: u64 = 10;
+
+    /// Creates an empty belt, staging buffers are allocated on demand.
+    pub fn new() -> Self {
+        Self {
+            frame_index: 0,
+            free_buffers: Vec::new(),
+            pending: Vec::new(),
+        }
+    }
+
+    /// Sets the frame index that newly scheduled readbacks are associated with.
+    pub fn begin_frame(&mut self, frame_index: u64) {
+        self.frame_index = frame_index;
+    }
+
+    /// Schedules a copy of a buffer region for readback.
+    ///
+    /// `source` needs `COPY_SRC` usage, `offset` and `size` need to be multiples of [`wgpu::COPY_BUFFER_ALIGNMENT`].
+    pub fn read_buffer(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        source: &wgpu::Buffer,
+        offset: wgpu::BufferAddress,
+        size: wgpu::BufferAddress,
+        callback: impl FnOnce(ReadbackData<'_>) + 'static,
+    ) {
+        let layout = ReadbackLayout::Linear { size };
+        let buffer = self.allocate_staging_buffer(device, layout.staging_size());
+        encoder.copy_buffer_to_buffer(source, offset, &buffer, 0, size);
+        self.push_pending(buffer, layout, callback);
+    }
+
+    /// Schedules a copy of a texture region for readback.
+    ///
+    /// The texture needs `COPY_SRC` usage.
+    /// The data passed to the callback has tightly packed rows of blocks.
+    pub fn read_texture(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        source: wgpu::ImageCopyTexture<'_>,
+        format: wgpu::TextureFormat,
+        extent: wgpu::Extent3d,
+        callback: impl FnOnce(ReadbackData<'_>) + 'static,
+    ) {
+        let block_size = format
+            .block_copy_size(Some(source.aspect))
+            .expect("Texture format & aspect combination can't be copied");
+        let (block_width, block_height) = format.block_dimensions();
+        let num_blocks_x = extent.width.div_ceil(block_width);
+        let num_blocks_y = extent.height.div_ceil(block_height);
+
+        let unpadded_bytes_per_row = num_blocks_x * block_size;
+        let padded_bytes_per_row =
+            unpadded_bytes_per_row.next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+        let layout = ReadbackLayout::Rows {
+            unpadded_bytes_per_row,
+            padded_bytes_per_row,
+            num_rows: num_blocks_y * extent.depth_or_array_layers,
+        };
+
+        let buffer = self.allocate_staging_buffer(device, layout.staging_size());
+        encoder.copy_texture_to_buffer(
+            source,
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: Some(num_blocks_y),
+                },
+            },
+            extent,
+        );
+        self.push_pending(buffer, layout, callback);
+    }
+
+    /// Starts mapping all buffers whose copies were recorded since the last call.
+    ///
+    /// Must be called after the command encoder containing the copies was submitted.
+    pub fn after_queue_submit(&mut self) {
+        for pending in &mut self.pending {
+            if pending.map_state.is_some() {
+                continue;
+            }
+
+            let map_state = Arc::new(AtomicU8::new(MAP_STATE_PENDING));
+            pending.map_state = Some(map_state.clone());
+            pending
+                .buffer
+                .slice(..pending.layout.staging_size())
+                .map_async(wgpu::MapMode::Read, move |result| {
+                    let state = if result.is_ok() {
+                        MAP_STATE_MAPPED
+                    } else {
+                        MAP_STATE_FAILED
+                    };
+                    map_state.store(state, Ordering::Release);
+                });
+        }
+    }
+
+    /// Polls the device and invokes callbacks for all readbacks that have finished.
+    ///
+    /// Does not block. On the web, mapping progresses regardless of polling.
+    pub fn poll(&mut self, device: &wgpu::Device) {
+        if self.pending.is_empty() {
+            return;
+        }
+        device.poll(wgpu::Maintain::Poll);
+
+        let mut still_pending = Vec::with_capacity(self.pending.len());
+        for pending in std::mem::take(&mut self.pending) {
+            let state = pending
+                .map_state
+                .as_ref()
+                .map_or(MAP_STATE_PENDING, |state| state.load(Ordering::Acquire));
+
+            match state {
+                MAP_STATE_MAPPED => self.finish_readback(pending),
+                MAP_STATE_FAILED => {
+                    log::error!(
+                        "Failed to map readback buffer scheduled on frame {}",
+                        pending.frame_index
+                    );
+                }
+                _ => {
+                    if self.frame_index.saturating_sub(pending.frame_index)
+                        == Self::LATENCY_WARNING_THRESHOLD
+                    {
+                        log::warn!(
+                            "Readback scheduled on frame {} is still outstanding after {} frames.",
+                            pending.frame_index,
+                            Self::LATENCY_WARNING_THRESHOLD
+                        );
+                    }
+                    still_pending.push(pending);
+                }
+            }
+        }
+        self.pending = still_pending;
+    }
+
+    /// Number of readbacks that haven't been handed out yet.
+    pub fn num_pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    fn finish_readback(&mut self, pending: PendingReadback) {
+        let PendingReadback {
+            buffer,
+            layout,
+            frame_index,
+            callback,
+            map_state: _,
+        } = pending;
+
+        {
+            let mapped = buffer.slice(..layout.staging_size()).get_mapped_range();
+            match layout {
+                ReadbackLayout::Linear { .. } => {
+                    callback(ReadbackData {
+                        frame_index,
+                        data: &mapped,
+                    });
+                }
+                ReadbackLayout::Rows {
+                    unpadded_bytes_per_row,
+                    padded_bytes_per_row,
+                    ..
+                } if unpadded_bytes_per_row == padded_bytes_per_row => {
+                    callback(ReadbackData {
+                        frame_index,
+                        data: &mapped,
+                    });
+                }
+                ReadbackLayout::Rows {
+                    unpadded_bytes_per_row,
+                    padded_bytes_per_row,
+                    num_rows,
+                } => {
+                    let mut packed =
+                        Vec::with_capacity(unpadded_bytes_per_row as usize * num_rows as usize);
+                    for row in mapped.chunks_exact(padded_bytes_per_row as usize) {
+                        packed.extend_from_slice(&row[..unpadded_bytes_per_row as usize]);
+                    }
+                    callback(ReadbackData {
+                        frame_index,
+                        data: &packed,
+                    });
+                }
+            }
+        }
+        buffer.unmap();
+
+        if self.free_buffers.len() < Self::MAX_FREE_BUFFERS {
+            self.free_buffers.push(buffer);
+        }
+    }
+
+    fn allocate_staging_buffer(&mut self, device: &wgpu::Device, size: u64) -> wgpu::Buffer {
+        // Pick the smallest free buffer that is large enough.
+        let best_fit = self
+            .free_buffers
+            .iter()
+            .enumerate()
+            .filter(|(_, buffer)| buffer.size() >= size)
+            .min_by_key(|(_, buffer)| buffer.size())
+            .map(|(index, _)| index);
+
+        if let Some(index) = best_fit {
+            return self.free_buffers.swap_remove(index);
+        }
+
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Readback staging buffer"),
+            size: size.next_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT),
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    fn push_pending(
+        &mut self,
+        buffer: wgpu::Buffer,
+        layout: ReadbackLayout,
+        callback: impl FnOnce(ReadbackData<'_>) + 'static,
+    ) {
+        self.pending.push(PendingReadback {
+            buffer,
+            layout,
+            frame_index: self.frame_index,
+            callback: Box::new(callback),
+            map_state: None,
+        });
+    }
+}
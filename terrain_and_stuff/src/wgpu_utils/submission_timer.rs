@@ -0,0 +1,282 @@
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use web_time::{Duration, Instant};
+
+/// A submission whose completion on the device timeline is awaited.
+struct PendingSubmission {
+    label: &'static str,
+    submitted: Instant,
+    completed: Arc<Mutex<Option<Instant>>>,
+}
+
+/// All submissions of a frame, in submission order.
+struct PendingFrame {
+    /// Completion of everything submitted before the frame, i.e. when the GPU could start with the frame at the earliest.
+    previous_work_completed: Arc<Mutex<Option<Instant>>>,
+    submissions: Vec<PendingSubmission>,
+}
+
+impl PendingFrame {
+    fn is_complete(&self) -> bool {
+        self.previous_work_completed.lock().is_some()
+            && self
+                .submissions
+                .iter()
+                .all(|submission| submission.completed.lock().is_some())
+    }
+}
+
+/// Accumulated durations of a scope since the last report.
+pub(super) struct ScopeStatistics {
+    label: &'static str,
+    total: Duration,
+    num_samples: u32,
+}
+
+/// Coarse per-pass GPU timings that don't require timestamp queries.
+///
+/// Every submission is a scope, [`wgpu::Queue::on_submitted_work_done`] fences
+/// record when it finished on the device timeline. Passes need to be submitted separately to be told apart.
+/// A scope's duration is the time between its completion and whatever happened later:
+/// the completion of the previous scope or its own submission.
+/// On the web, completion callbacks fire when the browser gets back to the event loop,
+/// so the timings include some latency and are only good for finding out which passes are expensive.
+///
+/// On native, the timings are not meaningful: callbacks only fire when the device is polled,
+/// which happens once per frame in [`SubmissionTimer::poll`]. All scopes of a frame then appear to complete at once,
+/// the last one reads about zero and the first one includes everything up to the poll.
+/// Use [`super::GpuTimer`] wherever timestamp queries are available instead and disable fence timing.
+///
+/// Measurements are only taken while reports are logged (once per second)
+/// or while the total GPU time of each frame is recorded.
+///
+/// Reports also contain the CPU time spent in calls that may block on the GPU or the compositor,
+/// i.e. surface acquisition, queue submission & present, to help with frame pacing.
+pub struct SubmissionTimer {
+    /// Whether submissions are timed via fences, otherwise only CPU timings are gathered.
+    time_submissions: bool,
+    log_reports: bool,
+    record_frame_times: bool,
+
+    pending_frames: Vec<PendingFrame>,
+    statistics: Vec<ScopeStatistics>,
+    cpu_statistics: Vec<ScopeStatistics>,
+    last_report: Instant,
+    frame_times: Vec<Duration>,
+}
+
+impl SubmissionTimer {
+    const REPORT_INTERVAL: Duration = Duration::from_secs(1);
+
+    /// Frames that never complete (e.g. after device loss) are dropped after this many frames.
+    const MAX_PENDING_FRAMES: usize = 16;
+
+    /// Frame times that weren't taken are dropped after this many frames.
+    const MAX_FRAME_TIMES: usize = 64;
+
+    /// Without `time_submissions` only CPU timings are gathered, e.g. because timestamp queries take care of the GPU.
+    pub fn new(time_submissions: bool) -> Self {
+        Self {
+            time_submissions,
+            log_reports: false,
+            record_frame_times: false,
+            pending_frames: Vec::new(),
+            statistics: Vec::new(),
+            cpu_statistics: Vec::new(),
+            last_report: Instant::now(),
+            frame_times: Vec::new(),
+        }
+    }
+
+    /// Whether submissions are currently timed via fences.
+    pub fn is_active(&self) -> bool {
+        self.time_submissions && (self.log_reports || self.record_frame_times)
+    }
+
+    /// Whether reports are logged, see [`SubmissionTimer::set_log_reports`].
+    pub fn logs_reports(&self) -> bool {
+        self.log_reports
+    }
+
+    /// Enables logging of averages once per second, see [`SubmissionTimer::poll`].
+    pub fn set_log_reports(&mut self, log_reports: bool) {
+        self.log_reports = log_reports;
+        self.statistics.clear();
+        self.cpu_statistics.clear();
+        self.last_report = Instant::now();
+    }
+
+    /// Enables recording of total GPU frame times, see [`SubmissionTimer::take_frame_times`].
+    pub fn set_record_frame_times(&mut self, record_frame_times: bool) {
+        self.record_frame_times = record_frame_times;
+        self.frame_times.clear();
+    }
+
+    /// Total GPU times of all frames completed since the last call, oldest first.
+    pub fn take_frame_times(&mut self) -> Vec<Duration> {
+        std::mem::take(&mut self.frame_times)
+    }
+
+    /// Starts timing a new frame. Must be called before any of the frame's submissions.
+    pub fn begin_frame(&mut self, queue: &wgpu::Queue) {
+        if !self.is_active() {
+            return;
+        }
+
+        if self.pending_frames.len() >= Self::MAX_PENDING_FRAMES {
+            self.pending_frames.remove(0);
+        }
+
+        let previous_work_completed = Arc::new(Mutex::new(None));
+        queue.on_submitted_work_done({
+            let previous_work_completed = previous_work_completed.clone();
+            move || *previous_work_completed.lock() = Some(Instant::now())
+        });
+
+        self.pending_frames.push(PendingFrame {
+            previous_work_completed,
+            submissions: Vec::new(),
+        });
+    }
+
+    /// Measures the CPU time of a call while reports are logged.
+    pub fn time_cpu<R>(&mut self, label: &'static str, f: impl FnOnce() -> R) -> R {
+        if !self.log_reports {
+            return f();
+        }
+
+        let start = Instant::now();
+        let result = f();
+        add_sample(&mut self.cpu_statistics, label, start.elapsed());
+        result
+    }
+
+    /// Submits a command buffer as a separately timed scope.
+    pub fn submit(
+        &mut self,
+        queue: &wgpu::Queue,
+        label: &'static str,
+        command_buffer: wgpu::CommandBuffer,
+    ) {
+        self.time_cpu("queue.submit", || queue.submit(Some(command_buffer)));
+
+        if !self.is_active() {
+            return;
+        }
+        let Some(frame) = self.pending_frames.last_mut() else {
+            return;
+        };
+
+        let completed = Arc::new(Mutex::new(None));
+        queue.on_submitted_work_done({
+            let completed = completed.clone();
+            move || *completed.lock() = Some(Instant::now())
+        });
+        frame.submissions.push(PendingSubmission {
+            label,
+            submitted: Instant::now(),
+            completed,
+        });
+    }
+
+    /// Gathers timings of all completed frames and logs a report if it is due.
+    pub fn poll(&mut self, device: &wgpu::Device) {
+        if !self.is_active() {
+            self.pending_frames.clear();
+            return;
+        }
+        if !self.pending_frames.is_empty() {
+            device.poll(wgpu::Maintain::Poll);
+        }
+
+        // Frames complete in order, so stop at the first incomplete one.
+        let num_complete_frames = self
+            .pending_frames
+            .iter()
+            .take_while(|frame| frame.is_complete())
+            .count();
+        for frame in self.pending_frames.drain(..num_complete_frames) {
+            let mut previous_completed = frame
+                .previous_work_completed
+                .lock()
+                .unwrap_or_else(Instant::now);
+            let mut frame_time = Duration::ZERO;
+
+            for submission in &frame.submissions {
+                let completed = submission.completed.lock().unwrap_or(previous_completed);
+                let started = previous_completed.max(submission.submitted);
+                let duration = completed.saturating_duration_since(started);
+                previous_completed = completed;
+                frame_time += duration;
+
+                if self.log_reports {
+                    add_sample(&mut self.statistics, submission.label, duration);
+                }
+            }
+
+            if self.record_frame_times {
+                if self.frame_times.len() >= Self::MAX_FRAME_TIMES {
+                    self.frame_times.remove(0);
+                }
+                self.frame_times.push(frame_time);
+            }
+        }
+
+        if self.log_reports
+            && self.last_report.elapsed() >= Self::REPORT_INTERVAL
+            && !self.cpu_statistics.is_empty()
+        {
+            if !self.statistics.is_empty() {
+                log::info!(
+                    "GPU submission timings (coarse, without timestamp queries):\n{}",
+                    format_report(&self.statistics)
+                );
+            }
+            log::info!(
+                "CPU timings (average per call):\n{}",
+                format_report(&self.cpu_statistics)
+            );
+
+            self.statistics.clear();
+            self.cpu_statistics.clear();
+            self.last_report = Instant::now();
+        }
+    }
+}
+
+pub(super) fn add_sample(
+    statistics: &mut Vec<ScopeStatistics>,
+    label: &'static str,
+    duration: Duration,
+) {
+    if let Some(statistics) = statistics
+        .iter_mut()
+        .find(|statistics| statistics.label == label)
+    {
+        statistics.total += duration;
+        statistics.num_samples += 1;
+    } else {
+        statistics.push(ScopeStatistics {
+            label,
+            total: duration,
+            num_samples: 1,
+        });
+    }
+}
+
+/// One line per scope with its average duration.
+pub(super) fn format_report(statistics: &[ScopeStatistics]) -> String {
+    statistics
+        .iter()
+        .map(|statistics| {
+            let average = statistics.total / statistics.num_samples;
+            format!(
+                "  {}: {:.3}ms",
+                statistics.label,
+                average.as_secs_f64() * 1000.0
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
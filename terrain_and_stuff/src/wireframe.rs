@@ -0,0 +1,78 @@
+//! Wireframe debug rendering of scene geometry.
+
+use crate::{
+    resource_managers::{PipelineError, PipelineManager, RenderPipelineHandle, ShaderEntryPoint},
+    wgpu_utils::GpuCapabilities,
+};
+
+/// How scene geometry is rasterized, for debugging.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum WireframeMode {
+    /// Regular shaded geometry.
+    #[default]
+    Off,
+
+    /// Only the wireframe, no shading.
+    Wireframe,
+
+    /// Wireframe drawn on top of shaded geometry.
+    Overlay,
+}
+
+impl WireframeMode {
+    /// Mode to cycle to, wrapping around.
+    pub fn next(self) -> Self {
+        match self {
+            Self::Off => Self::Wireframe,
+            Self::Wireframe => Self::Overlay,
+            Self::Overlay => Self::Off,
+        }
+    }
+
+    /// Whether the regular shaded geometry is drawn.
+    pub fn draws_shaded(self) -> bool {
+        self != Self::Wireframe
+    }
+
+    /// Whether a wireframe is drawn.
+    pub fn draws_wireframe(self) -> bool {
+        self != Self::Off
+    }
+}
+
+/// Creates a variant of a render pipeline that rasterizes lines in a constant color.
+///
+/// Returns `Ok(None)` if the device doesn't support line polygon mode.
+/// If the pipeline uses depth, the wireframe is depth tested against, but doesn't write depth,
+/// and is biased towards the camera so it can be drawn on top of the shaded geometry.
+pub fn create_wireframe_variant(
+    device: &wgpu::Device,
+    capabilities: &GpuCapabilities,
+    pipeline_manager: &mut PipelineManager,
+    pipeline: RenderPipelineHandle,
+) -> Result<Option<RenderPipelineHandle>, PipelineError> {
+    if !capabilities.polygon_mode_line {
+        return Ok(None);
+    }
+    let Some(descriptor) = pipeline_manager.render_pipeline_descriptor(pipeline) else {
+        return Ok(None);
+    };
+
+    let mut descriptor = descriptor.clone();
+    descriptor.debug_label += " (wireframe)";
+    descriptor.fragment_shader = ShaderEntryPoint::first_in("wireframe.wgsl");
+    descriptor.primitive.polygon_mode = wgpu::PolygonMode::Line;
+    descriptor.primitive.cull_mode = None;
+    if let Some(depth_stencil) = &mut descriptor.depth_stencil {
+        depth_stencil.depth_write_enabled = false;
+        depth_stencil.bias = wgpu::DepthBiasState {
+            constant: -1,
+            slope_scale: -1.0,
+            clamp: 0.0,
+        };
+    }
+
+    pipeline_manager
+        .create_render_pipeline(device, descriptor)
+        .map(Some)
+}
@@ -0,0 +1,289 @@
+//! Golden-image regression tests: renders canonical scenes offscreen and compares them against stored PNGs.
+//!
+//! Scenes are rendered at a small fixed resolution & time with whatever adapter is available,
+//! software rasterizers like llvmpipe included. If there's no adapter at all, the tests are skipped.
+//!
+//! To accept intentional changes, rerun with `UPDATE_GOLDEN_IMAGES=1` and commit the updated images in `tests/golden/`.
+//! On mismatch, the rendered image & a difference image are written to the cargo target directory for inspection.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use std::{cell::RefCell, path::PathBuf, rc::Rc};
+
+use terrain_renderer::{
+    global_bindings::{FrameUniformBuffer, GlobalBindings},
+    render_output::{screenshot, HdrBackbuffer},
+    resource_managers::PipelineManager,
+    sky::Sky,
+    triangle,
+    wgpu_utils::ReadbackBelt,
+};
+
+const RESOLUTION: glam::UVec2 = glam::uvec2(128, 72);
+/// Non-sRGB like the surface formats the app picks, since the display transform already encodes to sRGB.
+const OUTPUT_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+
+/// Simulation time all scenes are rendered at.
+const ELAPSED_TIME: f32 = 10.0;
+
+/// Maximum difference per channel, in 8 bit sRGB steps, for a pixel to count as matching.
+///
+/// Comparing display referred sRGB values keeps the tolerance roughly perceptually uniform.
+const CHANNEL_TOLERANCE: u8 = 3;
+
+/// Fraction of pixels that may exceed the channel tolerance,
+/// accounting for rasterization differences along edges between GPUs.
+const MAX_MISMATCHING_PIXEL_FRACTION: f64 = 0.002;
+
+const UPDATE_ENV_VAR: &str = "UPDATE_GOLDEN_IMAGES";
+
+struct Renderer {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    pipeline_manager: PipelineManager,
+    global_bindings: GlobalBindings,
+    hdr_backbuffer: HdrBackbuffer,
+    sky: Sky,
+    triangle: terrain_renderer::resource_managers::RenderPipelineHandle,
+}
+
+impl Renderer {
+    /// Returns `None` if there's no adapter to render with.
+    fn new() -> Option<Self> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
+        let adapter =
+            pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default()))?;
+        let (device, queue) = pollster::block_on(adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                label: Some("Golden image test device"),
+                ..Default::default()
+            },
+            None,
+        ))
+        .expect("Failed to create device");
+
+        let mut pipeline_manager = PipelineManager::new().expect("Create pipeline manager");
+        let global_bindings = GlobalBindings::new(&device);
+        let hdr_backbuffer =
+            HdrBackbuffer::new(&device, RESOLUTION, &mut pipeline_manager, OUTPUT_FORMAT)
+                .expect("Create HDR backbuffer");
+        let sky = Sky::new(&device, &mut pipeline_manager, &global_bindings).expect("Create sky");
+        let triangle =
+            triangle::create_render_pipeline(&device, &mut pipeline_manager, &global_bindings)
+                .expect("Create triangle pipeline");
+
+        Some(Self {
+            device,
+            queue,
+            pipeline_manager,
+            global_bindings,
+            hdr_backbuffer,
+            sky,
+            triangle,
+        })
+    }
+
+    /// Renders the sky & triangle scene and reads back the display transformed output.
+    fn render_scene(&mut self, dithering: bool) -> Vec<u8> {
+        let resolution = RESOLUTION.as_vec2();
+        self.global_bindings.update_frame_uniform_buffer(
+            &self.queue,
+            &FrameUniformBuffer {
+                resolution: resolution.into(),
+                inv_resolution: resolution.recip().into(),
+                jitter_offset: [0.0; 2],
+                frame_index: 0,
+                elapsed_time: ELAPSED_TIME,
+                delta_time: 0.0,
+                real_delta_time: 0.0,
+            },
+        );
+        self.hdr_backbuffer.set_dithering(dithering);
+
+        let target =
+            screenshot::create_target(&self.device, "Golden image", RESOLUTION, OUTPUT_FORMAT);
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Golden image encoder"),
+            });
+        {
+            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Scene"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: self.hdr_backbuffer.texture_view(),
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            rpass.set_bind_group(0, Some(self.global_bindings.bind_group()), &[]);
+            self.sky
+                .draw(&mut rpass, &self.pipeline_manager)
+                .expect("Sky pipeline is missing");
+            rpass.set_pipeline(
+                self.pipeline_manager
+                    .get_render_pipeline(self.triangle)
+                    .expect("Triangle pipeline is missing"),
+            );
+            rpass.draw(0..3, 0..1);
+        }
+        self.hdr_backbuffer
+            .display_transform(
+                &target.create_view(&wgpu::TextureViewDescriptor::default()),
+                None,
+                &mut encoder,
+                &self.pipeline_manager,
+            )
+            .expect("Display transform pipeline is missing");
+
+        let mut readback_belt = ReadbackBelt::new();
+        let output = Rc::new(RefCell::new(None));
+        readback_belt.read_texture(
+            &self.device,
+            &mut encoder,
+            target.as_image_copy(),
+            OUTPUT_FORMAT,
+            target.size(),
+            {
+                let output = output.clone();
+                move |readback| *output.borrow_mut() = Some(readback.data.to_vec())
+            },
+        );
+        self.queue.submit(Some(encoder.finish()));
+        readback_belt.after_queue_submit();
+
+        while readback_belt.num_pending() > 0 {
+            self.device.poll(wgpu::Maintain::Wait);
+            readback_belt.poll(&self.device);
+        }
+        output.take().expect("Readback failed")
+    }
+}
+
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+        .join(format!("{name}.png"))
+}
+
+fn read_png(path: &std::path::Path) -> (glam::UVec2, Vec<u8>) {
+    let decoder = png::Decoder::new(std::fs::File::open(path).expect("Open golden image"));
+    let mut reader = decoder.read_info().expect("Read golden image header");
+    let mut data = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut data).expect("Read golden image");
+    assert_eq!(
+        (info.color_type, info.bit_depth),
+        (png::ColorType::Rgba, png::BitDepth::Eight),
+        "Golden image {path:?} must be 8 bit RGBA"
+    );
+    data.truncate(info.buffer_size());
+    (glam::uvec2(info.width, info.height), data)
+}
+
+/// Compares a rendered image against its golden image, or replaces the golden image if requested.
+fn check_golden_image(name: &str, rendered: Vec<u8>) {
+    let path = golden_path(name);
+    if std::env::var_os(UPDATE_ENV_VAR).is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        screenshot::write_png(&path, RESOLUTION, OUTPUT_FORMAT, rendered).unwrap();
+        println!("Updated golden image {path:?}");
+        return;
+    }
+    assert!(
+        path.exists(),
+        "Golden image {path:?} is missing, run with {UPDATE_ENV_VAR}=1 to create it"
+    );
+
+    let (golden_resolution, golden) = read_png(&path);
+    assert_eq!(
+        golden_resolution, RESOLUTION,
+        "Golden image {path:?} has a different resolution"
+    );
+
+    let mut difference = Vec::with_capacity(rendered.len());
+    let mut num_mismatching_pixels = 0;
+    for (rendered, golden) in rendered.chunks_exact(4).zip(golden.chunks_exact(4)) {
+        let max_channel_difference = rendered
+            .iter()
+            .zip(golden)
+            .map(|(rendered, golden)| rendered.abs_diff(*golden))
+            .max()
+            .unwrap();
+        if max_channel_difference > CHANNEL_TOLERANCE {
+            num_mismatching_pixels += 1;
+        }
+        difference.extend_from_slice(&[
+            max_channel_difference,
+            max_channel_difference,
+            max_channel_difference,
+            255,
+        ]);
+    }
+
+    let num_pixels = (RESOLUTION.x * RESOLUTION.y) as usize;
+    let mismatching_fraction = num_mismatching_pixels as f64 / num_pixels as f64;
+    if mismatching_fraction > MAX_MISMATCHING_PIXEL_FRACTION {
+        let output_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("golden_images");
+        std::fs::create_dir_all(&output_dir).unwrap();
+        let rendered_path = output_dir.join(format!("{name}.png"));
+        let difference_path = output_dir.join(format!("{name}_difference.png"));
+        screenshot::write_png(&rendered_path, RESOLUTION, OUTPUT_FORMAT, rendered).unwrap();
+        screenshot::write_png(&difference_path, RESOLUTION, OUTPUT_FORMAT, difference).unwrap();
+
+        panic!(
+            "{num_mismatching_pixels} of {num_pixels} pixels differ from golden image {path:?} by more than {CHANNEL_TOLERANCE}.\n\
+            Rendered image: {rendered_path:?}\n\
+            Difference: {difference_path:?}\n\
+            If the change is intended, rerun with {UPDATE_ENV_VAR}=1."
+        );
+    }
+}
+
+fn run_golden_image_test(name: &str) {
+    let Some(mut renderer) = Renderer::new() else {
+        eprintln!("No wgpu adapter available, skipping golden image test {name:?}.");
+        return;
+    };
+    let rendered = renderer.render_scene(false);
+    check_golden_image(name, rendered);
+}
+
+#[test]
+fn sky_and_triangle() {
+    run_golden_image_test("sky_and_triangle");
+}
+
+/// Dithering stays below half a step, so it can't be told apart from a golden image within the channel tolerance.
+/// Instead, compare against the undithered output: dithering must change some pixels, but none by more than one step.
+#[test]
+fn dithering_changes_output_by_at_most_one_step() {
+    let Some(mut renderer) = Renderer::new() else {
+        eprintln!("No wgpu adapter available, skipping dithering test.");
+        return;
+    };
+    let undithered = renderer.render_scene(false);
+    let dithered = renderer.render_scene(true);
+
+    let differences = undithered
+        .iter()
+        .zip(&dithered)
+        .map(|(undithered, dithered)| undithered.abs_diff(*dithered));
+    let max_difference = differences.clone().max().unwrap_or(0);
+    let num_changed_channels = differences.filter(|difference| *difference > 0).count();
+
+    assert!(
+        max_difference <= 1,
+        "Dithering changed a channel by {max_difference} steps"
+    );
+    assert!(
+        num_changed_channels > 0,
+        "Dithering didn't change the output"
+    );
+}
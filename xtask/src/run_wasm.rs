@@ -76,6 +76,17 @@ pub fn run_wasm(shell: Shell, mut args: Arguments) -> anyhow::Result<()> {
     let release_flag: &[_] = if release { &["--release"] } else { &[] };
     let output_dir = if release { "release" } else { "debug" };
 
+    // Shaders are embedded into the web build and only compiled at runtime, so catch errors early.
+    log::info!("validating shaders");
+
+    xshell::cmd!(
+        shell,
+        "cargo run --bin {CRATE_NAME} {release_flag...} -- --validate-shaders"
+    )
+    .quiet()
+    .run()
+    .context("Shader validation failed")?;
+
     log::info!("building the application for wasm");
 
     let cargo_args = args.finish();